@@ -0,0 +1,5 @@
+SELECT FileExtension, COUNT(*), SUM(Size)
+FROM [./]
+WHERE Type = 'file'
+GROUP BY FileExtension
+ORDER BY SUM(Size) DESC
\ No newline at end of file
@@ -1,5 +1,3 @@
--- Not working currently
-
 SELECT * 
 FROM [./] currdir
 INNER JOIN [./../] prevdir ON prevdir.Type = currdir.Type
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT Name, Size
+FROM [./]
+WHERE Type = 'file'
+ORDER BY Size DESC, Name
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT
+    Size / 1024 AS kb
+    , Name AS file
+FROM [./]
+ORDER BY kb DESC, file
\ No newline at end of file
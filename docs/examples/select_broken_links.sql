@@ -0,0 +1,6 @@
+SELECT
+    Path
+    , LinkTarget
+FROM [./]
+WHERE Type = 'symlink'
+    AND IsBrokenLink
@@ -1,7 +1,6 @@
--- Does not work currently
-
-SELECT 
-    CASE WHEN Name = '1' THEN 1 ELSE 0 END
+SELECT
+    CASE WHEN Size > 1048576 THEN 'large' WHEN Size > 0 THEN 'small' ELSE 'empty' END AS SizeClass
+    , COUNT(*)
 FROM [./]
-
-SELECT CASE WHEN Name = '1' THEN 1 ELSE 0 END FROM [./]
\ No newline at end of file
+WHERE CASE Type WHEN 'file' THEN 1 ELSE 0 END = 1
+GROUP BY CASE WHEN Size > 1048576 THEN 'large' WHEN Size > 0 THEN 'small' ELSE 'empty' END
\ No newline at end of file
@@ -0,0 +1,6 @@
+SELECT
+    f.Name
+    , f.Size
+    , b.Size AS BackupSize
+FROM [./src/] f
+INNER JOIN [./backup/] b ON b.Name = f.Name AND b.Size <> f.Size
@@ -0,0 +1,10 @@
+-- The hash columns are only read from disk when a query uses them, SELECT * leaves them out
+SELECT
+    Hash_Sha256
+    , COUNT(*) AS Copies
+    , SUM(Size) AS TotalSize
+FROM [./]
+WHERE Type = 'file'
+GROUP BY Hash_Sha256
+HAVING COUNT(*) > 1
+ORDER BY TotalSize DESC
@@ -0,0 +1,4 @@
+SELECT Name FROM [./a/]
+EXCEPT
+SELECT Name FROM [./b/]
+ORDER BY Name
@@ -0,0 +1,5 @@
+SELECT
+    f.Name
+    , f.Size
+FROM [./src/] f
+WHERE NOT EXISTS (SELECT 1 FROM [./backup/] b WHERE b.Name = f.Name AND b.Size = f.Size)
@@ -0,0 +1,7 @@
+SELECT
+    Path
+    , Size
+FROM [./]
+WHERE Name LIKE '%.rs'
+    OR Name GLOB '*.tar.gz'
+    OR FileExtension REGEXP '^(md|toml)$'
@@ -0,0 +1,8 @@
+SET TIME ZONE LOCAL;
+
+SELECT
+    Name
+    , Modified
+FROM [./]
+WHERE Modified > '2026-01-01'
+ORDER BY Modified DESC
@@ -0,0 +1,9 @@
+-- Files anyone can write to, or whose owner no longer exists
+SELECT
+    Path
+    , Mode
+    , Uid
+    , Owner
+FROM [./]
+WHERE Mode & 2 <> 0
+    OR Owner IS NULL
@@ -0,0 +1,11 @@
+WITH RECURSIVE tree (Path, RelativePath, Depth, Type) AS (
+    SELECT Path, RelativePath, Depth, Type FROM [./]
+    UNION ALL
+    SELECT c.Path, c.RelativePath, c.Depth, c.Type
+    FROM tree t, t.Path c
+    WHERE t.Type = 'dir'
+)
+SELECT RelativePath, Depth
+FROM tree
+WHERE Type = 'file'
+ORDER BY RelativePath
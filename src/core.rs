@@ -2,4 +2,11 @@ pub mod file;
 pub mod dialect;
 pub mod error;
 pub mod column;
-pub mod expr_result;
+pub mod value;
+pub mod aggregate;
+pub mod result_set;
+pub mod row;
+pub mod pattern;
+pub mod hash;
+#[cfg(unix)]
+pub mod owner;
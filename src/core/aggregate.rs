@@ -0,0 +1,123 @@
+use std::{cmp::Ordering, collections::HashSet, str::FromStr};
+use super::{error::CoreError, value::*};
+
+/**
+ * Aggregate functions that reduce a group of rows to a single value
+**/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+impl FromStr for Aggregate {
+    type Err = CoreError;
+
+    fn from_str(input: &str) -> Result<Aggregate, Self::Err> {
+        match &input.to_ascii_lowercase()[..] {
+            "count" => Ok(Aggregate::Count),
+            "sum" => Ok(Aggregate::Sum),
+            "min" => Ok(Aggregate::Min),
+            "max" => Ok(Aggregate::Max),
+            "avg" => Ok(Aggregate::Avg),
+            _ => Err(CoreError::GeneralError(format!("No aggregate function matching {} was found", input))),
+        }
+    }
+}
+
+impl Aggregate {
+    /**
+     * Reduces the values of a group to a single value, NULL values are ignored
+     *
+     * MIN and MAX compare strings to timestamps as dates or times in the given time zone
+    **/
+    pub fn aggregate(&self, values: Vec<CoreValue>, distinct: bool, time_zone: TimeZoneSetting) -> Result<CoreValue, CoreError> {
+        let mut values: Vec<CoreValue> = values.into_iter().filter(|value| !value.is_null()).collect();
+        if distinct {
+            let mut seen = HashSet::new();
+            values.retain(|value| seen.insert(value.clone()));
+        }
+
+        if values.is_empty() && *self != Aggregate::Count {
+            return Ok(CoreValue::Null);
+        }
+
+        match self {
+            Aggregate::Count => { Ok(CoreValue::Integer(values.len() as i64)) }
+            Aggregate::Sum => { sum(&values) }
+            Aggregate::Avg => {
+                let total = sum(&values)?.to_f64().unwrap_or_default();
+                Ok(CoreValue::Float(total / values.len() as f64))
+            }
+            Aggregate::Min | Aggregate::Max => {
+                let wanted = if *self == Aggregate::Min { Ordering::Less } else { Ordering::Greater };
+                let mut result = values[0].clone();
+                for value in values.into_iter().skip(1) {
+                    if compare_values(&value, &result, time_zone)? == Some(wanted) {
+                        result = value;
+                    }
+                }
+
+                Ok(result)
+            }
+        }
+    }
+}
+
+/**
+ * Whole numbers are summed without losing precision, anything else is summed as a float
+**/
+fn sum(values: &[CoreValue]) -> Result<CoreValue, CoreError> {
+    let integers = values.iter().map(|value| match value {
+        CoreValue::Integer(integer) => { Some(*integer) }
+        _ => { None }
+    }).collect::<Option<Vec<i64>>>();
+
+    if let Some(integers) = integers {
+        return integers.into_iter().try_fold(0i64, |total, integer| total.checked_add(integer))
+            .map(CoreValue::Integer)
+            .ok_or_else(|| CoreError::GeneralError("Arithmetic overflow in SUM".to_owned()));
+    }
+
+    let mut total = 0.0;
+    for value in values {
+        match value {
+            CoreValue::Integer(_) | CoreValue::Float(_) => { total += value.to_f64().unwrap_or_default() }
+            _ => { return Err(CoreError::GeneralError("SUM and AVG require numeric values".to_owned())) }
+        }
+    }
+
+    Ok(CoreValue::Float(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(numbers: &[&str]) -> Vec<CoreValue> {
+        numbers.iter().map(|number| number.parse::<i64>().map(CoreValue::Integer).unwrap_or(CoreValue::Null)).collect()
+    }
+
+    #[test]
+    fn aggregate_count_ignores_null() {
+        assert_eq!(Aggregate::Count.aggregate(numbers(&["1", "NULL", "1"]), false, TimeZoneSetting::Utc), Ok(CoreValue::Integer(2)));
+        assert_eq!(Aggregate::Count.aggregate(numbers(&["1", "NULL", "1"]), true, TimeZoneSetting::Utc), Ok(CoreValue::Integer(1)));
+    }
+
+    #[test]
+    fn aggregate_sum_avg() {
+        assert_eq!(Aggregate::Sum.aggregate(numbers(&["4096", "10", "NULL"]), false, TimeZoneSetting::Utc), Ok(CoreValue::Integer(4106)));
+        assert_eq!(Aggregate::Sum.aggregate(numbers(&["3000000000", "3000000000"]), false, TimeZoneSetting::Utc), Ok(CoreValue::Integer(6_000_000_000)));
+        assert_eq!(Aggregate::Avg.aggregate(numbers(&["1", "2"]), false, TimeZoneSetting::Utc), Ok(CoreValue::Float(1.5)));
+        assert_eq!(Aggregate::Sum.aggregate(numbers(&["NULL"]), false, TimeZoneSetting::Utc), Ok(CoreValue::Null));
+    }
+
+    #[test]
+    fn aggregate_min_max() {
+        assert_eq!(Aggregate::Min.aggregate(numbers(&["10", "2", "30"]), false, TimeZoneSetting::Utc), Ok(CoreValue::Integer(2)));
+        assert_eq!(Aggregate::Max.aggregate(numbers(&["10", "2", "30"]), false, TimeZoneSetting::Utc), Ok(CoreValue::Integer(30)));
+    }
+}
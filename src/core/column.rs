@@ -1,8 +1,8 @@
-use std::{cmp::Ordering, error::Error, ffi::OsString, time::SystemTime, fmt::{Debug, Display}, fmt, fs::{self, DirEntry, File, Metadata}, io, iter::FromIterator, path::PathBuf, slice::Iter, str::FromStr};
+use std::{convert::TryFrom, cmp::Ordering, error::Error, ffi::{OsStr, OsString}, time::{SystemTime, UNIX_EPOCH}, mem::discriminant, fmt::{Debug, Display}, fmt, fs::{self, DirEntry, File, Metadata}, io, iter::FromIterator, path::PathBuf, slice::Iter, str::FromStr};
 use sqlparser::{parser::ParserError, ast::Query};
 use serde::{Serialize, ser::SerializeStruct, Serializer};
 use strum::{AsStaticRef, IntoEnumIterator};
-use super::{error::CoreError, file::file_type::FileType};
+use super::{error::CoreError, file::file_type::FileType, hash::{HashAlgorithm, file_hash}, value::{CoreValue, os_str_value}};
 
 pub trait FileColumnValue {
     fn column(&self, column: &FileColumn) -> FileColumn;
@@ -16,7 +16,30 @@ pub trait FileColumnValues {
     fn file_extension(&self) -> FileColumn;
     fn size(&self) -> FileColumn;
     fn absolute_path(&self) -> FileColumn;
+    fn parent(&self) -> FileColumn;
+    fn stem(&self) -> FileColumn;
+    fn extensions(&self) -> FileColumn;
+    fn depth(&self) -> FileColumn;
+    fn relative_path(&self) -> FileColumn;
+    fn is_hidden(&self) -> FileColumn;
     fn created(&self) -> FileColumn;
+    fn modified(&self) -> FileColumn;
+    fn accessed(&self) -> FileColumn;
+    fn changed(&self) -> FileColumn;
+    fn link_target(&self) -> FileColumn;
+    fn is_broken_link(&self) -> FileColumn;
+
+    /* The unix columns are NULL on platforms that do not have them */
+    fn mode(&self) -> FileColumn { FileColumn::Mode(None) }
+    fn permissions(&self) -> FileColumn { FileColumn::Permissions(None) }
+    fn uid(&self) -> FileColumn { FileColumn::Uid(None) }
+    fn gid(&self) -> FileColumn { FileColumn::Gid(None) }
+    fn owner(&self) -> FileColumn { FileColumn::Owner(None) }
+    fn group(&self) -> FileColumn { FileColumn::Group(None) }
+    fn inode(&self) -> FileColumn { FileColumn::Inode(None) }
+    fn device(&self) -> FileColumn { FileColumn::Device(None) }
+    fn hard_links(&self) -> FileColumn { FileColumn::HardLinks(None) }
+    fn blocks(&self) -> FileColumn { FileColumn::Blocks(None) }
 }
 
 #[derive(Debug, AsStaticStr, EnumIter, PartialEq, PartialOrd, Hash, Eq, Ord, Clone)]
@@ -28,12 +51,85 @@ pub enum FileColumn {
     FileExtension(Option<OsString>),
     Size(Option<u64>),
     AbsolutePath(Option<PathBuf>),
-    Created(Option<SystemTime>) // Switch SystemTime out with something more human display oriented
+    Parent(Option<PathBuf>),
+    /* The name without its last extension */
+    Stem(Option<OsString>),
+    /* Every extension of the name, such as tar.gz */
+    Extensions(Option<OsString>),
+    /* The number of directories below the queried directory, its files have a depth of 1 */
+    Depth(Option<u64>),
+    /* The path below the queried directory */
+    RelativePath(Option<PathBuf>),
+    IsHidden(Option<bool>),
+    Created(Option<SystemTime>),
+    Modified(Option<SystemTime>),
+    Accessed(Option<SystemTime>),
+    /* When the file's metadata last changed, the ctime on unix */
+    Changed(Option<SystemTime>),
+    /* The path a symlink points to, NULL when the file is not a symlink */
+    LinkTarget(Option<PathBuf>),
+    /* Whether the file is a symlink to a path that does not exist */
+    IsBrokenLink(Option<bool>),
+    /* The permission bits as a number for masks such as Mode & 2, displayed in octal such as 0755 */
+    Mode(Option<u32>),
+    /* The permission bits, written like ls such as rwxr-xr-x */
+    Permissions(Option<u32>),
+    Uid(Option<u32>),
+    Gid(Option<u32>),
+    /* The names of the user and group, NULL when the id no longer has a user or group */
+    Owner(Option<String>),
+    Group(Option<String>),
+    Inode(Option<u64>),
+    Device(Option<u64>),
+    HardLinks(Option<u64>),
+    /* The number of 512 byte blocks allocated to the file */
+    Blocks(Option<u64>),
+    /* Hashes of the file's contents, which are only read when the hash is used by the query */
+    #[strum(serialize = "Hash_Sha256")]
+    HashSha256(Option<String>),
+    #[strum(serialize = "Hash_Blake3")]
+    HashBlake3(Option<String>),
+    #[strum(serialize = "Hash_Md5")]
+    HashMd5(Option<String>),
+    #[strum(serialize = "Hash_Crc32")]
+    HashCrc32(Option<String>),
 }
 
 impl FileColumn {
+    /**
+     * The columns of a file, the hash columns are left out as reading every file's contents is slow so they must be selected by name
+    **/
     pub fn iterator() -> Iter<'static, FileColumn> {
-        [FileColumn::Name(None), FileColumn::Path(None), FileColumn::Type(None), FileColumn::FileExtension(None), FileColumn::Size(None), FileColumn::AbsolutePath(None), FileColumn::Created(None)].iter()
+        [FileColumn::Name(None), FileColumn::Path(None), FileColumn::Type(None), FileColumn::FileExtension(None), FileColumn::Size(None), FileColumn::AbsolutePath(None),
+            FileColumn::Parent(None), FileColumn::Stem(None), FileColumn::Extensions(None), FileColumn::Depth(None), FileColumn::RelativePath(None), FileColumn::IsHidden(None),
+            FileColumn::Created(None), FileColumn::Modified(None), FileColumn::Accessed(None), FileColumn::Changed(None), FileColumn::LinkTarget(None), FileColumn::IsBrokenLink(None),
+            FileColumn::Mode(None), FileColumn::Permissions(None), FileColumn::Uid(None), FileColumn::Gid(None), FileColumn::Owner(None), FileColumn::Group(None),
+            FileColumn::Inode(None), FileColumn::Device(None), FileColumn::HardLinks(None), FileColumn::Blocks(None)].iter()
+    }
+
+    /**
+     * The hash algorithm of a hash column
+    **/
+    pub fn hash_algorithm(&self) -> Option<HashAlgorithm> {
+        match self {
+            FileColumn::HashSha256(_) => { Some(HashAlgorithm::Sha256) }
+            FileColumn::HashBlake3(_) => { Some(HashAlgorithm::Blake3) }
+            FileColumn::HashMd5(_) => { Some(HashAlgorithm::Md5) }
+            FileColumn::HashCrc32(_) => { Some(HashAlgorithm::Crc32) }
+            _ => { None }
+        }
+    }
+
+    /**
+     * The hash column of the algorithm holding the hash
+    **/
+    pub fn from_hash(algorithm: HashAlgorithm, hash: Option<String>) -> FileColumn {
+        match algorithm {
+            HashAlgorithm::Sha256 => { FileColumn::HashSha256(hash) }
+            HashAlgorithm::Blake3 => { FileColumn::HashBlake3(hash) }
+            HashAlgorithm::Md5 => { FileColumn::HashMd5(hash) }
+            HashAlgorithm::Crc32 => { FileColumn::HashCrc32(hash) }
+        }
     }
 }
 
@@ -49,11 +145,82 @@ impl FromStr for FileColumn {
             "file_extension" | "fileextension" => Ok(FileColumn::FileExtension(None)),
             "size" => Ok(FileColumn::Size(None)),
             "absolutepath" | "absolute_path" => Ok(FileColumn::AbsolutePath(None)),
+            "parent" => Ok(FileColumn::Parent(None)),
+            "stem" => Ok(FileColumn::Stem(None)),
+            "extensions" => Ok(FileColumn::Extensions(None)),
+            "depth" => Ok(FileColumn::Depth(None)),
+            "relativepath" | "relative_path" => Ok(FileColumn::RelativePath(None)),
+            "ishidden" | "is_hidden" => Ok(FileColumn::IsHidden(None)),
+            "created" => Ok(FileColumn::Created(None)),
+            "modified" => Ok(FileColumn::Modified(None)),
+            "accessed" => Ok(FileColumn::Accessed(None)),
+            "changed" => Ok(FileColumn::Changed(None)),
+            "linktarget" | "link_target" => Ok(FileColumn::LinkTarget(None)),
+            "isbrokenlink" | "is_broken_link" => Ok(FileColumn::IsBrokenLink(None)),
+            "mode" => Ok(FileColumn::Mode(None)),
+            "permissions" => Ok(FileColumn::Permissions(None)),
+            "uid" => Ok(FileColumn::Uid(None)),
+            "gid" => Ok(FileColumn::Gid(None)),
+            "owner" => Ok(FileColumn::Owner(None)),
+            "group" => Ok(FileColumn::Group(None)),
+            "inode" => Ok(FileColumn::Inode(None)),
+            "device" => Ok(FileColumn::Device(None)),
+            "hardlinks" | "hard_links" => Ok(FileColumn::HardLinks(None)),
+            "blocks" => Ok(FileColumn::Blocks(None)),
+            "hash_sha256" | "hashsha256" => Ok(FileColumn::HashSha256(None)),
+            "hash_blake3" | "hashblake3" => Ok(FileColumn::HashBlake3(None)),
+            "hash_md5" | "hashmd5" => Ok(FileColumn::HashMd5(None)),
+            "hash_crc32" | "hashcrc32" => Ok(FileColumn::HashCrc32(None)),
             _ => Err(CoreError::GeneralError(format!("No type matching {} was found", str))),
         }
     }
 }
 
+/**
+ * A row of already loaded columns, the first column of the requested kind is returned
+ *
+ * A hash column is not loaded with the other columns, so it is computed from the row's path when it is requested
+**/
+impl FileColumnValue for Vec<FileColumn> {
+    fn column(&self, column: &FileColumn) -> FileColumn {
+        match (self.iter().find(|c| discriminant(*c) == discriminant(column)), column.hash_algorithm()) {
+            (Some(c), _) => { c.clone() }
+            (None, Some(algorithm)) => {
+                match self.column(&FileColumn::Path(None)) {
+                    FileColumn::Path(Some(path)) => { FileColumn::from_hash(algorithm, file_hash(&path, algorithm)) }
+                    _ => { FileColumn::Null }
+                }
+            }
+            (None, None) => { FileColumn::Null }
+        }
+    }
+
+    fn columns(&self) -> Vec<FileColumn> {
+        self.clone()
+    }
+}
+
+impl From<FileColumn> for CoreValue {
+    fn from(column: FileColumn) -> Self {
+        match column {
+            FileColumn::Name(Some(name)) | FileColumn::FileExtension(Some(name)) | FileColumn::Stem(Some(name)) | FileColumn::Extensions(Some(name)) => { os_str_value(&name) }
+            FileColumn::Path(Some(path)) | FileColumn::AbsolutePath(Some(path)) | FileColumn::LinkTarget(Some(path)) | FileColumn::Parent(Some(path)) | FileColumn::RelativePath(Some(path)) => { CoreValue::Path(path) }
+            FileColumn::IsBrokenLink(Some(b)) | FileColumn::IsHidden(Some(b)) => { CoreValue::Boolean(b) }
+            FileColumn::Type(Some(file_type)) => { CoreValue::String(file_type.to_string()) }
+            FileColumn::Size(Some(number)) | FileColumn::Depth(Some(number)) | FileColumn::Inode(Some(number)) | FileColumn::Device(Some(number)) | FileColumn::HardLinks(Some(number)) | FileColumn::Blocks(Some(number)) => {
+                i64::try_from(number).map(CoreValue::Integer).unwrap_or(CoreValue::Float(number as f64))
+            }
+            FileColumn::Mode(Some(mode)) => { CoreValue::Integer(i64::from(mode)) }
+            FileColumn::Permissions(Some(mode)) => { CoreValue::String(permissions(mode)) }
+            FileColumn::Uid(Some(id)) | FileColumn::Gid(Some(id)) => { CoreValue::Integer(i64::from(id)) }
+            FileColumn::Owner(Some(name)) | FileColumn::Group(Some(name)) => { CoreValue::String(name) }
+            FileColumn::HashSha256(Some(hash)) | FileColumn::HashBlake3(Some(hash)) | FileColumn::HashMd5(Some(hash)) | FileColumn::HashCrc32(Some(hash)) => { CoreValue::String(hash) }
+            FileColumn::Created(Some(time)) | FileColumn::Modified(Some(time)) | FileColumn::Accessed(Some(time)) | FileColumn::Changed(Some(time)) => { CoreValue::Timestamp(time) }
+            _ => { CoreValue::Null }
+        }
+    }
+}
+
 impl Serialize for FileColumn {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -68,16 +235,6 @@ impl Serialize for FileColumn {
     }
 }
 
-impl std::ops::Add for FileColumn {
-    type Output = FileColumn;
-    fn add(self, rhs: FileColumn) -> Self::Output {
-        match (self, rhs) {
-            (FileColumn::Size(Some(a)), FileColumn::Size(Some(b))) => { FileColumn::Size(Some(a + b)) }
-            _ => { unimplemented!() }
-        }
-    }
-}
-
 impl fmt::Display for FileColumn {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", match self {
@@ -87,8 +244,71 @@ impl fmt::Display for FileColumn {
             FileColumn::Type(typ) => { typ.clone().unwrap_or(FileType::File /* Give this type a default impl */).to_string() }
             FileColumn::FileExtension(ext) => { ext.clone().unwrap_or_default().to_str().unwrap_or_default().to_owned() }
             FileColumn::Size(size) => { size.unwrap_or_default().to_string() }
-            FileColumn::AbsolutePath(path) => { path.clone().unwrap_or_default().to_str().unwrap_or_default().to_owned() }
-            FileColumn::Created(created) => { "TODO".to_owned() }
+            FileColumn::AbsolutePath(path) | FileColumn::Parent(path) | FileColumn::RelativePath(path) => { path.clone().unwrap_or_default().to_str().unwrap_or_default().to_owned() }
+            FileColumn::Stem(name) | FileColumn::Extensions(name) => { name.clone().unwrap_or_default().to_str().unwrap_or_default().to_owned() }
+            FileColumn::Depth(depth) => { depth.map(|depth| depth.to_string()).unwrap_or_default() }
+            FileColumn::IsHidden(b) => { b.map(|b| b.to_string()).unwrap_or_default() }
+            FileColumn::Created(time) | FileColumn::Modified(time) | FileColumn::Accessed(time) | FileColumn::Changed(time) => {
+                time.map(|time| CoreValue::Timestamp(time).to_string()).unwrap_or_default()
+            }
+            FileColumn::LinkTarget(path) => { path.clone().unwrap_or_default().to_str().unwrap_or_default().to_owned() }
+            FileColumn::IsBrokenLink(is_broken) => { is_broken.map(|is_broken| is_broken.to_string()).unwrap_or_default() }
+            FileColumn::Mode(mode) => { mode.map(|mode| format!("{:04o}", mode)).unwrap_or_default() }
+            FileColumn::Permissions(mode) => { mode.map(permissions).unwrap_or_default() }
+            FileColumn::Uid(id) | FileColumn::Gid(id) => { id.map(|id| id.to_string()).unwrap_or_default() }
+            FileColumn::Owner(name) | FileColumn::Group(name) => { name.clone().unwrap_or_default() }
+            FileColumn::HashSha256(hash) | FileColumn::HashBlake3(hash) | FileColumn::HashMd5(hash) | FileColumn::HashCrc32(hash) => { hash.clone().unwrap_or_default() }
+            FileColumn::Inode(number) | FileColumn::Device(number) | FileColumn::HardLinks(number) | FileColumn::Blocks(number) => { number.map(|number| number.to_string()).unwrap_or_default() }
         })
     }
-}
\ No newline at end of file
+}
+
+/**
+ * Writes permission bits the way ls does, such as rwxr-xr-x
+ *
+ * The setuid, setgid and sticky bits replace the execute bit of their class with s or t, or S or T when the class cannot execute
+**/
+pub fn permissions(mode: u32) -> String {
+    let class = |read: u32, write: u32, execute: u32, special: u32, special_char: char| {
+        [
+            if mode & read != 0 { 'r' } else { '-' },
+            if mode & write != 0 { 'w' } else { '-' },
+            match (mode & execute != 0, mode & special != 0) {
+                (true, true) => { special_char }
+                (false, true) => { special_char.to_ascii_uppercase() }
+                (true, false) => { 'x' }
+                (false, false) => { '-' }
+            },
+        ]
+    };
+
+    class(0o400, 0o200, 0o100, 0o4000, 's').iter()
+        .chain(class(0o040, 0o020, 0o010, 0o2000, 's').iter())
+        .chain(class(0o004, 0o002, 0o001, 0o1000, 't').iter())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permissions_string() {
+        assert_eq!(permissions(0o755), "rwxr-xr-x");
+        assert_eq!(permissions(0o640), "rw-r-----");
+        assert_eq!(permissions(0o4755), "rwsr-xr-x");
+        assert_eq!(permissions(0o1777), "rwxrwxrwt");
+        assert_eq!(permissions(0o2644), "rw-r-Sr--");
+        assert_eq!(CoreValue::from(FileColumn::Mode(Some(0o644))), CoreValue::Integer(0o644));
+    }
+
+    #[test]
+    fn hash_column_from_path() {
+        let file = vec![FileColumn::Name(Some(OsString::from("3.md"))), FileColumn::Path(Some(PathBuf::from("./test/3.md")))];
+        let dir = vec![FileColumn::Name(Some(OsString::from("one"))), FileColumn::Path(Some(PathBuf::from("./test/one")))];
+
+        assert!(matches!(file.column(&FileColumn::HashCrc32(None)), FileColumn::HashCrc32(Some(_))));
+        assert_eq!(dir.column(&FileColumn::HashSha256(None)), FileColumn::HashSha256(None));
+        assert_eq!(FileColumn::from_str("hash_sha256").map(|column| column.as_static()), Ok("Hash_Sha256"));
+    }
+}
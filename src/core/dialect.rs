@@ -1,13 +1,18 @@
-use std::{cmp::Ordering, error::Error, str::FromStr, ffi::OsString, fmt::{Debug, Display}, fmt, fs::{self, DirEntry, File, FileType, Metadata}, io, iter::FromIterator, path::PathBuf, slice::Iter};
-use sqlparser::{parser::ParserError, ast::Query};
-use serde::{Serialize, ser::SerializeStruct, Serializer};
-use strum::{AsStaticRef, IntoEnumIterator};
-
+use std::{any::TypeId, iter::Peekable, str::{Chars, FromStr}};
+use sqlparser::{ast::{BinaryOperator, ColumnOption, Expr, Statement, UnaryOperator}, dialect::{Dialect, Precedence}, keywords::Keyword, parser::{Parser, ParserError}, tokenizer::Token};
 
+/**
+ * Wraps the dialect used to parse the SQL, adding the GLOB operator to it
+ *
+ * GLOB matches a name against a shell-style pattern such as `Name GLOB '*.tar.gz'`
+**/
+#[derive(Debug)]
 pub struct CoreDialect {
     pub dialect: Box<dyn sqlparser::dialect::Dialect>
 }
 
+pub const GLOB_OPERATOR: &str = "GLOB";
+
 impl FromStr for CoreDialect {
     type Err = ();
 
@@ -18,4 +23,157 @@ impl FromStr for CoreDialect {
             _ => { Err(()) }
         }
     }
-}
\ No newline at end of file
+}
+
+impl CoreDialect {
+    /**
+     * Whether [abc] in a LIKE pattern matches a single character in the set, as it does in MS SQL, rather than matching the brackets
+    **/
+    pub fn has_like_bracket_classes(&self) -> bool {
+        self.dialect.is::<sqlparser::dialect::MsSqlDialect>()
+    }
+
+    /**
+     * Whether the next tokens are GLOB or NOT GLOB
+    **/
+    fn is_glob(parser: &Parser) -> bool {
+        let is_glob_word = |token: Token| matches!(token, Token::Word(word) if word.keyword == Keyword::NoKeyword && word.value.eq_ignore_ascii_case(GLOB_OPERATOR));
+        match parser.peek_token().token {
+            Token::Word(word) if word.keyword == Keyword::NOT => { is_glob_word(parser.peek_nth_token(1).token) }
+            token => { is_glob_word(token) }
+        }
+    }
+}
+
+/**
+ * Forwards each method to the wrapped dialect
+**/
+macro_rules! delegate_to_dialect {
+    ($(fn $name:ident(&self $(, $arg:ident: $arg_type:ty)*) -> $return_type:ty;)*) => {
+        $(fn $name(&self $(, $arg: $arg_type)*) -> $return_type { self.dialect.$name($($arg),*) })*
+    };
+}
+
+/**
+ * Every method is forwarded to the wrapped dialect, so that it parses the same SQL as the wrapped dialect does apart from GLOB
+**/
+impl Dialect for CoreDialect {
+    fn parse_infix(&self, parser: &mut Parser, expr: &Expr, precedence: u8) -> Option<Result<Expr, ParserError>> {
+        if !CoreDialect::is_glob(parser) {
+            return self.dialect.parse_infix(parser, expr, precedence);
+        }
+
+        let negated = parser.parse_keyword(Keyword::NOT);
+        parser.next_token();
+
+        Some(parser.parse_subexpr(self.prec_value(Precedence::Like)).map(|pattern| {
+            let glob = Expr::BinaryOp { left: Box::new(expr.clone()), op: BinaryOperator::Custom(GLOB_OPERATOR.to_owned()), right: Box::new(pattern) };
+            if negated { Expr::UnaryOp { op: UnaryOperator::Not, expr: Box::new(glob) } } else { glob }
+        }))
+    }
+
+    fn get_next_precedence(&self, parser: &Parser) -> Option<Result<u8, ParserError>> {
+        if CoreDialect::is_glob(parser) {
+            return Some(Ok(self.prec_value(Precedence::Like)));
+        }
+
+        self.dialect.get_next_precedence(parser)
+    }
+
+    fn get_next_precedence_default(&self, parser: &Parser) -> Result<u8, ParserError> {
+        if CoreDialect::is_glob(parser) {
+            return Ok(self.prec_value(Precedence::Like));
+        }
+
+        self.dialect.get_next_precedence_default(parser)
+    }
+
+    delegate_to_dialect! {
+        // The wrapped dialect's type is used so the parser keeps its dialect specific syntax, such as TOP for MS SQL
+        fn dialect(&self) -> TypeId;
+        fn is_delimited_identifier_start(&self, ch: char) -> bool;
+        fn identifier_quote_style(&self, identifier: &str) -> Option<char>;
+        fn is_proper_identifier_inside_quotes(&self, chars: Peekable<Chars<'_>>) -> bool;
+        fn is_identifier_start(&self, ch: char) -> bool;
+        fn is_identifier_part(&self, ch: char) -> bool;
+        fn is_custom_operator_part(&self, ch: char) -> bool;
+        fn is_reserved_for_identifier(&self, keyword: Keyword) -> bool;
+        fn parse_prefix(&self, parser: &mut Parser) -> Option<Result<Expr, ParserError>>;
+        fn parse_statement(&self, parser: &mut Parser) -> Option<Result<Statement, ParserError>>;
+        fn parse_column_option(&self, parser: &mut Parser) -> Result<Option<Result<Option<ColumnOption>, ParserError>>, ParserError>;
+        fn prec_value(&self, precedence: Precedence) -> u8;
+        fn prec_unknown(&self) -> u8;
+        fn supports_string_literal_backslash_escape(&self) -> bool;
+        fn supports_unicode_string_literal(&self) -> bool;
+        fn supports_filter_during_aggregation(&self) -> bool;
+        fn supports_window_clause_named_window_reference(&self) -> bool;
+        fn supports_within_after_array_aggregation(&self) -> bool;
+        fn supports_group_by_expr(&self) -> bool;
+        fn supports_connect_by(&self) -> bool;
+        fn supports_match_recognize(&self) -> bool;
+        fn supports_in_empty_list(&self) -> bool;
+        fn supports_start_transaction_modifier(&self) -> bool;
+        fn supports_named_fn_args_with_eq_operator(&self) -> bool;
+        fn supports_named_fn_args_with_colon_operator(&self) -> bool;
+        fn supports_named_fn_args_with_assignment_operator(&self) -> bool;
+        fn supports_named_fn_args_with_rarrow_operator(&self) -> bool;
+        fn supports_named_fn_args_with_expr_name(&self) -> bool;
+        fn supports_numeric_prefix(&self) -> bool;
+        fn supports_window_function_null_treatment_arg(&self) -> bool;
+        fn supports_dictionary_syntax(&self) -> bool;
+        fn support_map_literal_syntax(&self) -> bool;
+        fn supports_lambda_functions(&self) -> bool;
+        fn supports_methods(&self) -> bool;
+        fn supports_parenthesized_set_variables(&self) -> bool;
+        fn supports_select_wildcard_except(&self) -> bool;
+        fn convert_type_before_value(&self) -> bool;
+        fn supports_triple_quoted_string(&self) -> bool;
+        fn supports_trailing_commas(&self) -> bool;
+        fn supports_limit_comma(&self) -> bool;
+        fn supports_projection_trailing_commas(&self) -> bool;
+        fn supports_object_name_double_dot_notation(&self) -> bool;
+        fn supports_struct_literal(&self) -> bool;
+        fn describe_requires_table_keyword(&self) -> bool;
+        fn allow_extract_custom(&self) -> bool;
+        fn allow_extract_single_quotes(&self) -> bool;
+        fn supports_create_index_with_clause(&self) -> bool;
+        fn require_interval_qualifier(&self) -> bool;
+        fn supports_explain_with_utility_options(&self) -> bool;
+        fn supports_asc_desc_in_column_definition(&self) -> bool;
+        fn supports_factorial_operator(&self) -> bool;
+        fn supports_eq_alias_assignment(&self) -> bool;
+        fn supports_try_convert(&self) -> bool;
+        fn supports_bang_not_operator(&self) -> bool;
+        fn supports_listen_notify(&self) -> bool;
+        fn supports_load_data(&self) -> bool;
+        fn supports_load_extension(&self) -> bool;
+        fn supports_top_before_distinct(&self) -> bool;
+        fn supports_boolean_literals(&self) -> bool;
+        fn supports_show_like_before_in(&self) -> bool;
+        fn supports_comment_on(&self) -> bool;
+        fn supports_create_table_select(&self) -> bool;
+        fn supports_partiql(&self) -> bool;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_glob() {
+        let dialect = CoreDialect::from_str("mssql").expect("Dialect exists");
+        let statements = Parser::parse_sql(&dialect, "SELECT TOP 1 Name FROM [./] WHERE Name NOT GLOB '*.tar.gz' AND Size > 0").expect("SQL is valid");
+
+        assert_eq!(statements[0].to_string(), "SELECT TOP 1 Name FROM [./] WHERE NOT Name GLOB '*.tar.gz' AND Size > 0");
+    }
+
+    #[test]
+    fn parse_dialect_syntax() {
+        let parse = |dialect: &str, sql: &str| Parser::parse_sql(&CoreDialect::from_str(dialect).expect("Dialect exists"), sql).map(|statements| statements[0].to_string());
+
+        assert_eq!(parse("generic", "SELECT Name FROM \"./test/\" WHERE Name GLOB '*.md'"), Ok("SELECT Name FROM \"./test/\" WHERE Name GLOB '*.md'".to_owned()));
+        assert_eq!(parse("mssql", "SELECT TOP 1 Name FROM [./test/] WHERE Name GLOB '*.md'"), Ok("SELECT TOP 1 Name FROM [./test/] WHERE Name GLOB '*.md'".to_owned()));
+        assert!(CoreDialect::from_str("mysql").is_err());
+    }
+}
@@ -3,6 +3,8 @@ use sqlparser::parser;
 use serde::{Serialize, ser::SerializeStruct, Serializer};
 use strum::{AsStaticRef, IntoEnumIterator};
 
+/* The variants are named after the errors they wrap, such as an io::Error or a ParserError */
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum CoreError {
     IOError(String),
@@ -41,7 +43,7 @@ impl From<parser::ParserError> for CoreError {
 
 impl From<&str> for CoreError {
     fn from(e: &str) -> Self {
-        CoreError::GeneralError(format!("{}", e))
+        CoreError::GeneralError(e.to_string())
     }
 }
 
@@ -1,23 +1,49 @@
-use std::{cmp::Ordering, error::Error, ffi::OsString, path::Path, fmt::{Debug, Display}, fmt::{self, Formatter}, fs::{self, DirEntry, File, Metadata}, io, iter::FromIterator, path::PathBuf, slice::Iter, str::FromStr};
+use std::{cell::OnceCell, cmp::Ordering, error::Error, ffi::{OsStr, OsString}, path::Path, fmt::{Debug, Display}, fmt::{self, Formatter}, fs::{self, DirEntry, File, Metadata}, io, iter::FromIterator, path::PathBuf, slice::Iter, str::FromStr, time::{Duration, UNIX_EPOCH}};
 use sqlparser::{ast::{BinaryOperator, Expr, Ident, Query}, parser::ParserError};
 use serde::{Serialize, ser::SerializeStruct, Serializer};
 use strum::{AsStaticRef, IntoEnumIterator};
 use self::file_type::FileType;
-use super::{error::CoreError, column::*};
+use super::{error::CoreError, column::*, hash};
+#[cfg(unix)]
+use super::owner;
 
 pub mod file_type {
     #[derive(Debug, Clone, PartialEq, PartialOrd, Hash, Eq, Ord)]
     pub enum FileType {
         File,
         Dir,
+        Symlink,
+        Fifo,
+        Socket,
+        BlockDevice,
+        CharDevice,
     }
     
+    /**
+     * The type of the entry itself, a symlink is a symlink whatever it points to
+    **/
     impl From<std::fs::FileType> for FileType {
         fn from(file_type: std::fs::FileType) -> Self {
-            if file_type.is_file() {
-                FileType::File
-            } else {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::FileTypeExt;
+                if file_type.is_fifo() {
+                    return FileType::Fifo;
+                } else if file_type.is_socket() {
+                    return FileType::Socket;
+                } else if file_type.is_block_device() {
+                    return FileType::BlockDevice;
+                } else if file_type.is_char_device() {
+                    return FileType::CharDevice;
+                }
+            }
+
+            if file_type.is_symlink() {
+                FileType::Symlink
+            } else if file_type.is_dir() {
                 FileType::Dir
+            } else {
+                FileType::File
             }
         }
     }
@@ -26,10 +52,15 @@ pub mod file_type {
         fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
             write!(
                 f,
-                "Error: {}",
+                "{}",
                 match self {
                     FileType::File => "file",
-                    FileType::Dir => "directory",
+                    FileType::Dir => "dir",
+                    FileType::Symlink => "symlink",
+                    FileType::Fifo => "fifo",
+                    FileType::Socket => "socket",
+                    FileType::BlockDevice => "block_device",
+                    FileType::CharDevice => "char_device",
                 }
             )
         }
@@ -42,64 +73,269 @@ pub struct CoreFile {
     pub path: Option<PathBuf>,
     pub file_type: Option<FileType>,
     pub file_extension: Option<String>,
+    /* The directory that was queried, the relative path and depth are below it */
+    pub root: Option<PathBuf>,
 }
 
 impl CoreFile {
-    fn metadata(&self) -> Result<Metadata, io::Error> {
+    fn relative_path_buf(&self) -> Option<PathBuf> {
+        match (&self.path, &self.root) {
+            (Some(path), Some(root)) => { path.strip_prefix(root).ok().map(Path::to_path_buf) }
+            _ => { None }
+        }
+    }
+
+    /**
+     * The metadata of the entry itself, a symlink is not followed so this describes the link rather than what it points to
+    **/
+    fn link_metadata(&self) -> Result<Metadata, io::Error> {
         match &self.path {
-            Some(path) => { fs::metadata(&path) }
+            Some(path) => { fs::symlink_metadata(path) }
             None => { Err(io::Error::new(io::ErrorKind::NotFound, "File not found")) }
         }
     }
 }
 
-impl FileColumnValues for CoreFile {
+/**
+ * A file along with its metadata, which is fetched the first time a column needs it
+ *
+ * Type, LinkTarget and IsBrokenLink describe a symlink itself, every other column describes what it points to
+ * so a symlink has the size, times, mode, owner and inode of its target, and NULL for them when it is broken
+**/
+struct FileMetadata<'a> {
+    file: &'a CoreFile,
+    link_metadata: OnceCell<Option<Metadata>>,
+    metadata: OnceCell<Option<Metadata>>,
+}
+
+impl<'a> FileMetadata<'a> {
+    fn new(file: &'a CoreFile) -> Self {
+        FileMetadata { file, link_metadata: OnceCell::new(), metadata: OnceCell::new() }
+    }
+
+    fn link_metadata(&self) -> Option<&Metadata> {
+        self.link_metadata.get_or_init(|| self.file.link_metadata().ok()).as_ref()
+    }
+
+    /**
+     * The metadata of what a symlink points to, which is only fetched again for a symlink
+    **/
+    fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.get_or_init(|| match (&self.file.path, self.link_metadata()) {
+            (Some(path), Some(metadata)) if metadata.file_type().is_symlink() => { fs::metadata(path).ok() }
+            (_, metadata) => { metadata.cloned() }
+        }).as_ref()
+    }
+}
+
+/**
+ * Forwards each column to the file's metadata
+**/
+macro_rules! delegate_to_metadata {
+    ($($name:ident),* $(,)?) => {
+        $(fn $name(&self) -> FileColumn { FileMetadata::new(self).$name() })*
+    };
+}
+
+impl CoreFile {
+    fn hash(&self, column: &FileColumn) -> FileColumn {
+        match (column.hash_algorithm(), &self.path) {
+            (Some(algorithm), Some(path)) => { FileColumn::from_hash(algorithm, hash::file_hash(path, algorithm)) }
+            (Some(algorithm), None) => { FileColumn::from_hash(algorithm, None) }
+            (None, _) => { FileColumn::Null }
+        }
+    }
+}
+
+impl FileColumnValues for FileMetadata<'_> {
     fn size(&self) -> FileColumn {
-        FileColumn::Size(match &self.path {
-            Some(path) => { 
-                if let Ok(metadata) = fs::metadata(&path) {
-                    Some(metadata.len())
-                } else { None }
-            }
-            None => { None }
-        })
+        FileColumn::Size(self.metadata().map(|metadata| metadata.len()))
     }
     
+    /**
+     * The canonical path of the directory the file is in joined with the file's name, so a symlink is not resolved to its target
+    **/
     fn absolute_path(&self) -> FileColumn {
-        self.path()
+        FileColumn::AbsolutePath(match (&self.file.path, &self.file.name) {
+            (Some(path), Some(name)) => { path.parent().and_then(|parent| fs::canonicalize(if parent.as_os_str().is_empty() { Path::new(".") } else { parent }).ok()).map(|parent| parent.join(name)) }
+            (Some(path), None) => { fs::canonicalize(path).ok() }
+            (None, _) => { None }
+        })
+    }
+
+    fn parent(&self) -> FileColumn {
+        FileColumn::Parent(self.file.path.as_ref().and_then(|path| path.parent()).map(Path::to_path_buf))
+    }
+
+    fn stem(&self) -> FileColumn {
+        FileColumn::Stem(self.file.name.as_ref().and_then(|name| Path::new(name).file_stem()).map(OsStr::to_owned))
+    }
+
+    /**
+     * Everything after the first . of the name, a leading . is part of a hidden file's name rather than an extension
+    **/
+    fn extensions(&self) -> FileColumn {
+        FileColumn::Extensions(self.file.name.as_ref().and_then(|name| {
+            let mut stem = Path::new(name);
+            let mut extensions = Vec::new();
+            while let (Some(extension), Some(next_stem)) = (stem.extension(), stem.file_stem()) {
+                if next_stem.as_encoded_bytes().iter().all(|b| *b == b'.') {
+                    break;
+                }
+                extensions.push(extension);
+                stem = Path::new(next_stem);
+            }
+
+            let mut joined = OsString::new();
+            for (i, extension) in extensions.iter().rev().enumerate() {
+                if i > 0 {
+                    joined.push(".");
+                }
+                joined.push(extension);
+            }
+            Some(joined).filter(|extensions| !extensions.is_empty())
+        }))
+    }
+
+    fn depth(&self) -> FileColumn {
+        FileColumn::Depth(self.file.relative_path_buf().map(|path| path.components().count() as u64))
+    }
+
+    fn relative_path(&self) -> FileColumn {
+        FileColumn::RelativePath(self.file.relative_path_buf())
+    }
+
+    #[cfg(windows)]
+    fn is_hidden(&self) -> FileColumn {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        FileColumn::IsHidden(self.metadata().map(|metadata| metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0))
+    }
+
+    #[cfg(not(windows))]
+    fn is_hidden(&self) -> FileColumn {
+        FileColumn::IsHidden(self.file.name.as_ref().map(|name| name.as_encoded_bytes().starts_with(b".")))
     }
 
     fn name(&self) -> FileColumn {
-        FileColumn::Name(self.name.clone())
+        FileColumn::Name(self.file.name.clone())
     }
 
     fn path(&self) -> FileColumn {
-        FileColumn::Path(self.path.clone())
+        FileColumn::Path(self.file.path.clone())
     }
 
     fn file_type(&self) -> FileColumn {
-        FileColumn::Type(if let Ok(metadata) = self.metadata() {
-            if metadata.is_file() {
-                Some(FileType::File)
-            } else {
-                Some(FileType::Dir)
-            }
-        } else { None })
+        FileColumn::Type(self.link_metadata().map(|metadata| FileType::from(metadata.file_type())))
     }
 
-    fn file_extension(&self) -> FileColumn {
-        FileColumn::FileExtension(match &self.name {
-            Some(name) => { Path::new(&name.to_string_lossy().to_string()).extension().map_or(None, |f| Some(f.to_owned())) }
+    fn link_target(&self) -> FileColumn {
+        FileColumn::LinkTarget(match (&self.file.path, self.link_metadata()) {
+            (Some(path), Some(metadata)) if metadata.file_type().is_symlink() => { fs::read_link(path).ok() }
+            _ => { None }
+        })
+    }
+
+    fn is_broken_link(&self) -> FileColumn {
+        FileColumn::IsBrokenLink(match self.link_metadata() {
+            Some(metadata) if metadata.file_type().is_symlink() => { Some(self.metadata().is_none()) }
+            Some(_) => { Some(false) }
             None => { None }
         })
     }
 
+    fn file_extension(&self) -> FileColumn {
+        FileColumn::FileExtension(self.file.name.as_ref().and_then(|name| Path::new(name).extension()).map(OsStr::to_owned))
+    }
+
     fn created(&self) -> FileColumn {
-        FileColumn::Created(self.metadata().unwrap().created().ok())
+        FileColumn::Created(self.metadata().and_then(|metadata| metadata.created().ok()))
+    }
+
+    fn modified(&self) -> FileColumn {
+        FileColumn::Modified(self.metadata().and_then(|metadata| metadata.modified().ok()))
+    }
+
+    fn accessed(&self) -> FileColumn {
+        FileColumn::Accessed(self.metadata().and_then(|metadata| metadata.accessed().ok()))
+    }
+
+    #[cfg(unix)]
+    fn changed(&self) -> FileColumn {
+        use std::os::unix::fs::MetadataExt;
+        FileColumn::Changed(match self.metadata() {
+            Some(metadata) if metadata.ctime() >= 0 => { UNIX_EPOCH.checked_add(Duration::new(metadata.ctime() as u64, metadata.ctime_nsec() as u32)) }
+            _ => { None }
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn changed(&self) -> FileColumn {
+        FileColumn::Changed(None)
+    }
+
+    #[cfg(unix)]
+    fn mode(&self) -> FileColumn {
+        use std::os::unix::fs::MetadataExt;
+        FileColumn::Mode(self.metadata().map(|metadata| metadata.mode() & 0o7777))
+    }
+
+    #[cfg(unix)]
+    fn permissions(&self) -> FileColumn {
+        use std::os::unix::fs::MetadataExt;
+        FileColumn::Permissions(self.metadata().map(|metadata| metadata.mode() & 0o7777))
+    }
+
+    #[cfg(unix)]
+    fn uid(&self) -> FileColumn {
+        use std::os::unix::fs::MetadataExt;
+        FileColumn::Uid(self.metadata().map(|metadata| metadata.uid()))
+    }
+
+    #[cfg(unix)]
+    fn gid(&self) -> FileColumn {
+        use std::os::unix::fs::MetadataExt;
+        FileColumn::Gid(self.metadata().map(|metadata| metadata.gid()))
+    }
+
+    #[cfg(unix)]
+    fn owner(&self) -> FileColumn {
+        use std::os::unix::fs::MetadataExt;
+        FileColumn::Owner(self.metadata().and_then(|metadata| owner::user_name(metadata.uid())))
+    }
+
+    #[cfg(unix)]
+    fn group(&self) -> FileColumn {
+        use std::os::unix::fs::MetadataExt;
+        FileColumn::Group(self.metadata().and_then(|metadata| owner::group_name(metadata.gid())))
+    }
+
+    #[cfg(unix)]
+    fn inode(&self) -> FileColumn {
+        use std::os::unix::fs::MetadataExt;
+        FileColumn::Inode(self.metadata().map(|metadata| metadata.ino()))
+    }
+
+    #[cfg(unix)]
+    fn device(&self) -> FileColumn {
+        use std::os::unix::fs::MetadataExt;
+        FileColumn::Device(self.metadata().map(|metadata| metadata.dev()))
+    }
+
+    #[cfg(unix)]
+    fn hard_links(&self) -> FileColumn {
+        use std::os::unix::fs::MetadataExt;
+        FileColumn::HardLinks(self.metadata().map(|metadata| metadata.nlink()))
+    }
+
+    #[cfg(unix)]
+    fn blocks(&self) -> FileColumn {
+        use std::os::unix::fs::MetadataExt;
+        FileColumn::Blocks(self.metadata().map(|metadata| metadata.blocks()))
     }
 }
 
-impl FileColumnValue for CoreFile {
+impl FileColumnValue for FileMetadata<'_> {
     fn column(&self, column: &FileColumn) -> FileColumn {
         match column {
             FileColumn::Name(_) => { self.name() }
@@ -108,7 +344,29 @@ impl FileColumnValue for CoreFile {
             FileColumn::FileExtension(_) => { self.file_extension() }
             FileColumn::Size(_) => { self.size() }
             FileColumn::AbsolutePath(_) => { self.absolute_path() }
+            FileColumn::Parent(_) => { self.parent() }
+            FileColumn::Stem(_) => { self.stem() }
+            FileColumn::Extensions(_) => { self.extensions() }
+            FileColumn::Depth(_) => { self.depth() }
+            FileColumn::RelativePath(_) => { self.relative_path() }
+            FileColumn::IsHidden(_) => { self.is_hidden() }
             FileColumn::Created(_) => { self.created() }
+            FileColumn::Modified(_) => { self.modified() }
+            FileColumn::Accessed(_) => { self.accessed() }
+            FileColumn::Changed(_) => { self.changed() }
+            FileColumn::LinkTarget(_) => { self.link_target() }
+            FileColumn::IsBrokenLink(_) => { self.is_broken_link() }
+            FileColumn::Mode(_) => { self.mode() }
+            FileColumn::Permissions(_) => { self.permissions() }
+            FileColumn::Uid(_) => { self.uid() }
+            FileColumn::Gid(_) => { self.gid() }
+            FileColumn::Owner(_) => { self.owner() }
+            FileColumn::Group(_) => { self.group() }
+            FileColumn::Inode(_) => { self.inode() }
+            FileColumn::Device(_) => { self.device() }
+            FileColumn::HardLinks(_) => { self.hard_links() }
+            FileColumn::Blocks(_) => { self.blocks() }
+            column if column.hash_algorithm().is_some() => { self.file.hash(column) }
             _ => { FileColumn::Null }
         }
     }
@@ -121,11 +379,52 @@ impl FileColumnValue for CoreFile {
             self.file_extension(),
             self.size(),
             self.absolute_path(),
-            self.created()
+            self.parent(),
+            self.stem(),
+            self.extensions(),
+            self.depth(),
+            self.relative_path(),
+            self.is_hidden(),
+            self.created(),
+            self.modified(),
+            self.accessed(),
+            self.changed(),
+            self.link_target(),
+            self.is_broken_link(),
+            self.mode(),
+            self.permissions(),
+            self.uid(),
+            self.gid(),
+            self.owner(),
+            self.group(),
+            self.inode(),
+            self.device(),
+            self.hard_links(),
+            self.blocks()
         ]
     }
 }
 
+/**
+ * Every column of a file is read through its metadata, so the metadata is fetched once however many columns are read
+**/
+impl FileColumnValues for CoreFile {
+    delegate_to_metadata! {
+        name, path, file_type, file_extension, size, absolute_path, parent, stem, extensions, depth, relative_path, is_hidden, created, modified, accessed, changed,
+        link_target, is_broken_link, mode, permissions, uid, gid, owner, group, inode, device, hard_links, blocks
+    }
+}
+
+impl FileColumnValue for CoreFile {
+    fn column(&self, column: &FileColumn) -> FileColumn {
+        FileMetadata::new(self).column(column)
+    }
+
+    fn columns(&self) -> Vec<FileColumn> {
+        FileMetadata::new(self).columns()
+    }
+}
+
 impl Serialize for CoreFile {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -143,17 +442,22 @@ impl Serialize for CoreFile {
 impl Display for CoreFile {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         for column in FileColumn::iter() {
-            writeln!(f, "{}: {}", column, self.column(&column).to_string())?;
+            writeln!(f, "{}: {}", column, self.column(&column))?;
         }
 
-        return Ok(());
+        Ok(())
     }
 }
 
+/**
+ * A file read from a directory, the directory is the root of its relative path
+**/
 impl From<DirEntry> for CoreFile {
     fn from(dir: DirEntry) -> Self {
+        let path = dir.path();
         CoreFile {
-            path: Some(dir.path()),
+            root: path.parent().map(Path::to_path_buf),
+            path: Some(path),
             name: Some(dir.file_name()),
             file_type: if let Ok(file_type) = dir.file_type() { Some(FileType::from(file_type)) } else { None } ,
             file_extension: None
@@ -161,3 +465,138 @@ impl From<DirEntry> for CoreFile {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_file_columns() {
+        let file = CoreFile { name: Some(OsString::from("missing.txt")), path: Some(PathBuf::from("./test/missing.txt")), file_type: None, file_extension: None, root: None };
+
+        assert_eq!(file.created(), FileColumn::Created(None));
+        assert_eq!(file.modified(), FileColumn::Modified(None));
+        assert_eq!(file.accessed(), FileColumn::Accessed(None));
+        assert_eq!(file.changed(), FileColumn::Changed(None));
+        assert_eq!(file.size(), FileColumn::Size(None));
+        assert_eq!(file.mode(), FileColumn::Mode(None));
+        assert_eq!(file.owner(), FileColumn::Owner(None));
+        assert_eq!(file.is_broken_link(), FileColumn::IsBrokenLink(None));
+    }
+
+    #[test]
+    fn path_columns() {
+        let file = CoreFile { name: Some(OsString::from("archive.tar.gz")), path: Some(PathBuf::from("./test/one/archive.tar.gz")), file_type: None, file_extension: None, root: Some(PathBuf::from("./test")) };
+
+        assert_eq!(file.parent(), FileColumn::Parent(Some(PathBuf::from("./test/one"))));
+        assert_eq!(file.stem(), FileColumn::Stem(Some(OsString::from("archive.tar"))));
+        assert_eq!(file.file_extension(), FileColumn::FileExtension(Some(OsString::from("gz"))));
+        assert_eq!(file.extensions(), FileColumn::Extensions(Some(OsString::from("tar.gz"))));
+        assert_eq!(file.relative_path(), FileColumn::RelativePath(Some(PathBuf::from("one/archive.tar.gz"))));
+        assert_eq!(file.depth(), FileColumn::Depth(Some(2)));
+        assert_eq!(file.absolute_path(), FileColumn::AbsolutePath(Some(fs::canonicalize("./test/one").expect("Test directory exists").join("archive.tar.gz"))));
+
+        let hidden = CoreFile { name: Some(OsString::from(".bashrc")), path: None, file_type: None, file_extension: None, root: None };
+        assert_eq!(hidden.extensions(), FileColumn::Extensions(None));
+        let extensions = |name: &str| CoreFile { name: Some(OsString::from(name)), path: None, file_type: None, file_extension: None, root: None }.extensions();
+        assert_eq!(extensions(".bashrc.bak"), FileColumn::Extensions(Some(OsString::from("bak"))));
+        assert_eq!(extensions("a..b"), FileColumn::Extensions(Some(OsString::from(".b"))));
+        assert_eq!(extensions("..a"), FileColumn::Extensions(None));
+        assert_eq!(extensions("README"), FileColumn::Extensions(None));
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let non_utf8 = CoreFile { name: Some(OsStr::from_bytes(b"caf\xe9.tar.\xe9").to_owned()), path: None, file_type: None, file_extension: None, root: None };
+            assert_eq!(non_utf8.extensions(), FileColumn::Extensions(Some(OsStr::from_bytes(b"tar.\xe9").to_owned())));
+            assert_eq!(non_utf8.file_extension(), FileColumn::FileExtension(Some(OsStr::from_bytes(b"\xe9").to_owned())));
+        }
+        assert_eq!(hidden.stem(), FileColumn::Stem(Some(OsString::from(".bashrc"))));
+        assert_eq!(hidden.depth(), FileColumn::Depth(None));
+        #[cfg(not(windows))]
+        assert_eq!(hidden.is_hidden(), FileColumn::IsHidden(Some(true)));
+    }
+
+    #[test]
+    fn columns_share_metadata() {
+        let file = CoreFile { name: Some(OsString::from("3.md")), path: Some(PathBuf::from("./test/3.md")), file_type: None, file_extension: None, root: Some(PathBuf::from("./test")) };
+        let columns = file.columns();
+
+        assert_eq!(columns.len(), FileColumn::iterator().count());
+        for column in &columns {
+            assert_eq!(&file.column(column), column);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_columns() {
+        let dir = std::env::temp_dir().join(format!("systemsql_symlink_columns_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("Temp directory is writable");
+        let link = dir.join("broken");
+        let _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink(dir.join("missing.txt"), &link).expect("Symlink is created");
+
+        let file = CoreFile { name: Some(OsString::from("broken")), path: Some(link.clone()), file_type: None, file_extension: None, root: None };
+        assert_eq!(file.file_type(), FileColumn::Type(Some(FileType::Symlink)));
+        assert_eq!(file.link_target(), FileColumn::LinkTarget(Some(dir.join("missing.txt"))));
+        assert_eq!(file.is_broken_link(), FileColumn::IsBrokenLink(Some(true)));
+
+        let dir_file = CoreFile { name: None, path: Some(dir.clone()), file_type: None, file_extension: None, root: None };
+        assert_eq!(dir_file.file_type(), FileColumn::Type(Some(FileType::Dir)));
+        assert_eq!(dir_file.link_target(), FileColumn::LinkTarget(None));
+        assert_eq!(dir_file.is_broken_link(), FileColumn::IsBrokenLink(Some(false)));
+
+        fs::remove_dir_all(&dir).expect("Temp directory is removed");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_target_columns() {
+        let dir = std::env::temp_dir().join(format!("systemsql_symlink_target_columns_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("Temp directory is writable");
+        let target = dir.join("target.txt");
+        fs::write(&target, "0123456789").expect("Target is written");
+        let link = dir.join("link");
+        let _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink(&target, &link).expect("Symlink is created");
+
+        let file = CoreFile { name: Some(OsString::from("link")), path: Some(link.clone()), file_type: None, file_extension: None, root: None };
+        let target_file = CoreFile { name: Some(OsString::from("target.txt")), path: Some(target.clone()), file_type: None, file_extension: None, root: None };
+        assert_eq!(file.file_type(), FileColumn::Type(Some(FileType::Symlink)));
+        assert_eq!(file.is_broken_link(), FileColumn::IsBrokenLink(Some(false)));
+        assert_eq!(file.size(), FileColumn::Size(Some(10)));
+        assert_eq!(file.modified(), target_file.modified());
+        assert_eq!(file.mode(), target_file.mode());
+        assert_eq!(file.inode(), target_file.inode());
+
+        let broken = dir.join("broken");
+        let _ = fs::remove_file(&broken);
+        std::os::unix::fs::symlink(dir.join("missing.txt"), &broken).expect("Symlink is created");
+        let broken_file = CoreFile { name: Some(OsString::from("broken")), path: Some(broken), file_type: None, file_extension: None, root: None };
+        assert_eq!(broken_file.size(), FileColumn::Size(None));
+        assert_eq!(broken_file.mode(), FileColumn::Mode(None));
+
+        fs::remove_dir_all(&dir).expect("Temp directory is removed");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_hash_columns() {
+        let dir = std::env::temp_dir().join(format!("systemsql_symlink_hash_columns_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("Temp directory is writable");
+        let target = dir.join("target.txt");
+        fs::write(&target, "abc").expect("Target is written");
+        for (name, points_to) in [("link", target.clone()), ("broken", dir.join("missing.txt")), ("dir_link", dir.clone())].iter() {
+            let _ = fs::remove_file(dir.join(name));
+            std::os::unix::fs::symlink(points_to, dir.join(name)).expect("Symlink is created");
+        }
+
+        let hash = |name: &str| CoreFile { name: Some(OsString::from(name)), path: Some(dir.join(name)), file_type: None, file_extension: None, root: None }.column(&FileColumn::HashCrc32(None));
+        assert_eq!(hash("link"), FileColumn::HashCrc32(Some("352441c2".to_owned())));
+        assert_eq!(hash("link"), hash("target.txt"));
+        assert_eq!(hash("broken"), FileColumn::HashCrc32(None));
+        assert_eq!(hash("dir_link"), FileColumn::HashCrc32(None));
+
+        fs::remove_dir_all(&dir).expect("Temp directory is removed");
+    }
+}
@@ -0,0 +1,126 @@
+use std::{cell::RefCell, collections::HashMap, fs::{self, File}, io::{self, Read}, path::{Path, PathBuf}};
+use sha2::Digest;
+
+/* Files are read in chunks so that large files are not held in memory */
+const CHUNK_SIZE: usize = 64 * 1024;
+
+thread_local! {
+    /* A hash can be referenced by the select list, GROUP BY and HAVING of the same query, so each file is only read once per statement */
+    static HASH_CACHE: RefCell<HashMap<(PathBuf, HashAlgorithm), Option<String>>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    Sha256,
+    Blake3,
+    Md5,
+    Crc32,
+}
+
+/**
+ * Hashes the contents of a file, written as lowercase hex
+ *
+ * Only regular files are hashed, a symlink has the hash of the file it points to.
+ * A directory or other special file, a broken symlink or a file that cannot be read has no hash
+**/
+pub fn file_hash(path: &Path, algorithm: HashAlgorithm) -> Option<String> {
+    let key = (path.to_path_buf(), algorithm);
+    if let Some(hash) = HASH_CACHE.with(|cache| cache.borrow().get(&key).cloned()) {
+        return hash;
+    }
+
+    let hash = match fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => { hash_file(path, algorithm).ok() }
+        _ => { None }
+    };
+    HASH_CACHE.with(|cache| cache.borrow_mut().insert(key, hash.clone()));
+
+    hash
+}
+
+/**
+ * Forgets the hashes of the last statement, so a file that changed since is read again and the cache does not grow with every statement
+**/
+pub fn clear_cache() {
+    HASH_CACHE.with(|cache| cache.borrow_mut().clear());
+}
+
+fn hash_file(path: &Path, algorithm: HashAlgorithm) -> Result<String, io::Error> {
+    let file = File::open(path)?;
+    match algorithm {
+        HashAlgorithm::Sha256 => {
+            let mut hasher = sha2::Sha256::new();
+            read_chunks(file, |chunk| hasher.update(chunk))?;
+            Ok(hex(&hasher.finalize()))
+        }
+        HashAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            read_chunks(file, |chunk| { hasher.update(chunk); })?;
+            Ok(hasher.finalize().to_hex().to_string())
+        }
+        HashAlgorithm::Md5 => {
+            let mut hasher = md5::Md5::new();
+            read_chunks(file, |chunk| hasher.update(chunk))?;
+            Ok(hex(&hasher.finalize()))
+        }
+        HashAlgorithm::Crc32 => {
+            let mut hasher = crc32fast::Hasher::new();
+            read_chunks(file, |chunk| hasher.update(chunk))?;
+            Ok(format!("{:08x}", hasher.finalize()))
+        }
+    }
+}
+
+fn read_chunks(mut file: File, mut update: impl FnMut(&[u8])) -> Result<(), io::Error> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => { return Ok(()) }
+            Ok(read) => { update(&buffer[..read]) }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => { return Err(e) }
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_hashes() {
+        let path = std::env::temp_dir().join(format!("systemsql_file_hashes_{}.txt", std::process::id()));
+        fs::write(&path, "abc").expect("Temp file is writable");
+
+        assert_eq!(file_hash(&path, HashAlgorithm::Sha256), Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_owned()));
+        assert_eq!(file_hash(&path, HashAlgorithm::Blake3), Some("6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85".to_owned()));
+        assert_eq!(file_hash(&path, HashAlgorithm::Md5), Some("900150983cd24fb0d6963f7d28e17f72".to_owned()));
+        assert_eq!(file_hash(&path, HashAlgorithm::Crc32), Some("352441c2".to_owned()));
+
+        fs::remove_file(&path).expect("Temp file is removed");
+    }
+
+    #[test]
+    fn clear_cache_rereads_file() {
+        let path = std::env::temp_dir().join(format!("systemsql_clear_cache_{}.txt", std::process::id()));
+        fs::write(&path, "abc").expect("Temp file is writable");
+        assert_eq!(file_hash(&path, HashAlgorithm::Crc32), Some("352441c2".to_owned()));
+
+        fs::write(&path, "abcd").expect("Temp file is writable");
+        assert_eq!(file_hash(&path, HashAlgorithm::Crc32), Some("352441c2".to_owned()));
+        clear_cache();
+        assert_eq!(file_hash(&path, HashAlgorithm::Crc32), Some("ed82cd11".to_owned()));
+
+        fs::remove_file(&path).expect("Temp file is removed");
+    }
+
+    #[test]
+    fn directory_has_no_hash() {
+        assert_eq!(file_hash(Path::new("./test/one"), HashAlgorithm::Sha256), None);
+        assert_eq!(file_hash(Path::new("./test/missing.txt"), HashAlgorithm::Crc32), None);
+    }
+}
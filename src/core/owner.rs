@@ -0,0 +1,66 @@
+use std::{cell::RefCell, collections::HashMap, ffi::CStr, mem::MaybeUninit, os::raw::c_char, ptr};
+
+thread_local! {
+    /* The files of a directory are usually owned by a few users, so each user and group is only looked up once */
+    static USER_NAMES: RefCell<HashMap<u32, Option<String>>> = RefCell::new(HashMap::new());
+    static GROUP_NAMES: RefCell<HashMap<u32, Option<String>>> = RefCell::new(HashMap::new());
+}
+
+const INITIAL_BUFFER_SIZE: usize = 1024;
+const MAX_BUFFER_SIZE: usize = 1024 * 1024;
+
+/**
+ * The name of the user with the id, None when no user has the id such as a user that has been removed
+**/
+pub fn user_name(uid: u32) -> Option<String> {
+    USER_NAMES.with(|names| names.borrow_mut().entry(uid).or_insert_with(|| lookup_user_name(uid)).clone())
+}
+
+/**
+ * The name of the group with the id, None when no group has the id
+**/
+pub fn group_name(gid: u32) -> Option<String> {
+    GROUP_NAMES.with(|names| names.borrow_mut().entry(gid).or_insert_with(|| lookup_group_name(gid)).clone())
+}
+
+fn lookup_user_name(uid: u32) -> Option<String> {
+    let mut buffer = vec![0 as c_char; INITIAL_BUFFER_SIZE];
+    loop {
+        let mut passwd = MaybeUninit::<libc::passwd>::uninit();
+        let mut result: *mut libc::passwd = ptr::null_mut();
+        // SAFETY: the buffer outlives the call and its length is passed with it, the name is only read when an entry was found
+        let error = unsafe { libc::getpwuid_r(uid, passwd.as_mut_ptr(), buffer.as_mut_ptr(), buffer.len(), &mut result) };
+        match error {
+            libc::ERANGE if buffer.len() < MAX_BUFFER_SIZE => { buffer.resize(buffer.len() * 2, 0) }
+            0 if !result.is_null() => { return Some(unsafe { CStr::from_ptr((*result).pw_name) }.to_string_lossy().into_owned()) }
+            _ => { return None }
+        }
+    }
+}
+
+fn lookup_group_name(gid: u32) -> Option<String> {
+    let mut buffer = vec![0 as c_char; INITIAL_BUFFER_SIZE];
+    loop {
+        let mut group = MaybeUninit::<libc::group>::uninit();
+        let mut result: *mut libc::group = ptr::null_mut();
+        // SAFETY: the buffer outlives the call and its length is passed with it, the name is only read when an entry was found
+        let error = unsafe { libc::getgrgid_r(gid, group.as_mut_ptr(), buffer.as_mut_ptr(), buffer.len(), &mut result) };
+        match error {
+            libc::ERANGE if buffer.len() < MAX_BUFFER_SIZE => { buffer.resize(buffer.len() * 2, 0) }
+            0 if !result.is_null() => { return Some(unsafe { CStr::from_ptr((*result).gr_name) }.to_string_lossy().into_owned()) }
+            _ => { return None }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_names() {
+        assert_eq!(user_name(0), Some("root".to_owned()));
+        assert!(group_name(0).is_some());
+        assert_eq!(user_name(u32::MAX - 1), None);
+    }
+}
@@ -0,0 +1,224 @@
+use std::{cell::RefCell, collections::HashMap, iter::Peekable, str::Chars};
+use regex::bytes::{Regex, RegexBuilder};
+use super::error::CoreError;
+
+/* Matches any single byte, so that names that are not valid UTF-8 can still be matched by a wildcard */
+const ANY_BYTES: &str = "(?-u:.)*";
+const ANY_CHAR: &str = "(?:.|(?-u:.))";
+
+thread_local! {
+    /* The same pattern is usually matched against every row, so each pattern is only compiled once */
+    static REGEX_CACHE: RefCell<HashMap<(String, bool), Regex>> = RefCell::new(HashMap::new());
+}
+
+/**
+ * A LIKE, GLOB or REGEXP pattern compiled to a regular expression
+ *
+ * Patterns are matched against the raw bytes of a name or path, so names that are not valid UTF-8 are not lossily converted first
+**/
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    regex: Regex,
+}
+
+impl Pattern {
+    /**
+     * A LIKE pattern, % matches any number of characters and _ matches a single character
+     *
+     * With bracket classes, as in MS SQL, [abc] and [a-c] match a single character in the set and [^abc] a single character not in it.
+     * Otherwise [ matches itself. The escape character matches the character that follows it literally, such as '50!%' ESCAPE '!'
+    **/
+    pub fn like(pattern: &str, escape_char: Option<&str>, case_insensitive: bool, bracket_classes: bool) -> Result<Pattern, CoreError> {
+        let escape_char = match escape_char.map(|escape_char| escape_char.chars().collect::<Vec<char>>()).as_deref() {
+            Some([escape_char]) => { Some(*escape_char) }
+            Some(_) => { return Err(CoreError::GeneralError(format!("The escape character '{}' must be a single character", escape_char.unwrap_or_default()))) }
+            None => { None }
+        };
+
+        let mut regex = String::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                c if Some(c) == escape_char => {
+                    match chars.next() {
+                        Some(escaped) => { regex.push_str(&regex::escape(escaped.encode_utf8(&mut [0; 4]))) }
+                        None => { return Err(CoreError::GeneralError(format!("The LIKE pattern '{}' ends with its escape character", pattern))) }
+                    }
+                }
+                '%' => { regex.push_str(ANY_BYTES) }
+                '_' => { regex.push_str(ANY_CHAR) }
+                '[' if bracket_classes => { regex.push_str(&char_class(&mut chars, '^')) }
+                c => { regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))) }
+            }
+        }
+
+        Pattern::anchored(&regex, case_insensitive)
+    }
+
+    /**
+     * A shell-style GLOB pattern, * matches any number of characters and ? matches a single character
+     *
+     * [abc] and [a-c] match a single character in the set, and [!abc] or [^abc] a single character not in it. GLOB is case-sensitive
+    **/
+    pub fn glob(pattern: &str) -> Result<Pattern, CoreError> {
+        let mut regex = String::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => { regex.push_str(ANY_BYTES) }
+                '?' => { regex.push_str(ANY_CHAR) }
+                '[' => { regex.push_str(&char_class(&mut chars, '!')) }
+                c => { regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))) }
+            }
+        }
+
+        Pattern::anchored(&regex, false)
+    }
+
+    /**
+     * A regular expression, which matches when it is found anywhere in the value
+    **/
+    pub fn regexp(pattern: &str) -> Result<Pattern, CoreError> {
+        Ok(Pattern { regex: compile(pattern, false)? })
+    }
+
+    pub fn is_match(&self, bytes: &[u8]) -> bool {
+        self.regex.is_match(bytes)
+    }
+
+    fn anchored(regex: &str, case_insensitive: bool) -> Result<Pattern, CoreError> {
+        Ok(Pattern { regex: compile(&format!(r"(?s)\A{}\z", regex), case_insensitive)? })
+    }
+}
+
+fn compile(regex: &str, case_insensitive: bool) -> Result<Regex, CoreError> {
+    REGEX_CACHE.with(|cache| {
+        let key = (regex.to_owned(), case_insensitive);
+        if let Some(compiled) = cache.borrow().get(&key) {
+            return Ok(compiled.clone());
+        }
+
+        let compiled = RegexBuilder::new(regex).case_insensitive(case_insensitive).build()
+            .map_err(|e| CoreError::GeneralError(format!("The pattern '{}' is invalid: {}", regex, e)))?;
+        cache.borrow_mut().insert(key, compiled.clone());
+
+        Ok(compiled)
+    })
+}
+
+/**
+ * Converts the set of characters following a [ into a regex character class
+ *
+ * A ] straight after the [ is part of the set, and a [ that is never closed matches itself
+**/
+fn char_class(chars: &mut Peekable<Chars>, negation: char) -> String {
+    let mut lookahead = chars.clone();
+    let negated = lookahead.next_if(|c| *c == negation || *c == '^').is_some();
+
+    let mut set = Vec::new();
+    if let Some(c) = lookahead.next_if_eq(&']') {
+        set.push(c);
+    }
+
+    loop {
+        match lookahead.next() {
+            Some(']') => { break }
+            Some(c) => { set.push(c) }
+            None => { return regex::escape("[") }
+        }
+    }
+
+    *chars = lookahead;
+
+    let mut class = String::from(if negated { "[^" } else { "[" });
+    let mut i = 0;
+    while i < set.len() {
+        class.push_str(&regex::escape(set[i].encode_utf8(&mut [0; 4])));
+        // A - between two characters is a range, otherwise it matches itself
+        if i + 2 < set.len() && set[i + 1] == '-' {
+            class.push('-');
+            class.push_str(&regex::escape(set[i + 2].encode_utf8(&mut [0; 4])));
+            i += 3;
+        } else {
+            i += 1;
+        }
+    }
+    class.push(']');
+
+    class
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn like_pattern() {
+        let pattern = Pattern::like("%.r_", None, false, true).expect("Pattern is valid");
+        assert!(pattern.is_match(b"main.rs"));
+        assert!(!pattern.is_match(b"main.RS"));
+        assert!(!pattern.is_match(b"main.rst"));
+
+        assert!(Pattern::like("%.RS", None, true, true).expect("Pattern is valid").is_match(b"main.rs"));
+        assert!(Pattern::like("a.b*c", None, false, true).expect("Pattern is valid").is_match(b"a.b*c"));
+        assert!(!Pattern::like("a.b", None, false, true).expect("Pattern is valid").is_match(b"axb"));
+    }
+
+    #[test]
+    fn like_pattern_bracket_classes() {
+        assert!(Pattern::like("[a-c]%", None, false, true).expect("Pattern is valid").is_match(b"build.rs"));
+        assert!(!Pattern::like("[^a-c]%", None, false, true).expect("Pattern is valid").is_match(b"build.rs"));
+        assert!(Pattern::like("[draft]%", None, false, true).expect("Pattern is valid").is_match(b"d.txt"));
+        assert!(!Pattern::like("[draft]%", None, false, true).expect("Pattern is valid").is_match(b"[draft] notes.txt"));
+    }
+
+    #[test]
+    fn like_pattern_without_bracket_classes() {
+        assert!(Pattern::like("[draft]%", None, false, false).expect("Pattern is valid").is_match(b"[draft] notes.txt"));
+        assert!(!Pattern::like("[draft]%", None, false, false).expect("Pattern is valid").is_match(b"d.txt"));
+        assert!(Pattern::like("[a-c]_", None, false, false).expect("Pattern is valid").is_match(b"[a-c]1"));
+    }
+
+    #[test]
+    fn like_pattern_escape() {
+        let pattern = Pattern::like("50!%%", Some("!"), false, true).expect("Pattern is valid");
+        assert!(pattern.is_match(b"50%.txt"));
+        assert!(!pattern.is_match(b"500.txt"));
+
+        assert!(Pattern::like("a!", Some("!"), false, true).is_err());
+        assert!(Pattern::like("a", Some("!!"), false, true).is_err());
+    }
+
+    #[test]
+    fn glob_pattern() {
+        let pattern = Pattern::glob("*.tar.gz").expect("Pattern is valid");
+        assert!(pattern.is_match(b"archive.tar.gz"));
+        assert!(!pattern.is_match(b"archive.tar.GZ"));
+        assert!(!pattern.is_match(b"archive.tgz"));
+
+        assert!(Pattern::glob("file?.[!a-c]").expect("Pattern is valid").is_match(b"file1.d"));
+        assert!(!Pattern::glob("file?.[!a-c]").expect("Pattern is valid").is_match(b"file1.b"));
+        assert!(Pattern::glob("[]]*").expect("Pattern is valid").is_match(b"]a"));
+        assert!(Pattern::glob("[ab").expect("Pattern is valid").is_match(b"[ab"));
+        assert!(Pattern::glob("100%_*").expect("Pattern is valid").is_match(b"100%_done"));
+    }
+
+    #[test]
+    fn regexp_pattern() {
+        let pattern = Pattern::regexp(r"\.(rs|toml)$").expect("Pattern is valid");
+        assert!(pattern.is_match(b"Cargo.toml"));
+        assert!(!pattern.is_match(b"Cargo.lock"));
+
+        assert!(Pattern::regexp("(").is_err());
+    }
+
+    #[test]
+    fn pattern_non_utf8() {
+        let name = b"caf\xe9.txt";
+
+        assert!(Pattern::like("caf_.txt", None, false, true).expect("Pattern is valid").is_match(name));
+        assert!(Pattern::like("%.txt", None, false, true).expect("Pattern is valid").is_match(name));
+        assert!(Pattern::glob("caf*").expect("Pattern is valid").is_match(name));
+        assert!(Pattern::regexp(r"\.txt$").expect("Pattern is valid").is_match(name));
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+use super::value::{CoreValue, TimeZoneSetting};
+
+/**
+ * The rows returned by a query along with the name of each column
+ *
+ * Each column has the format its values are displayed in, a column without one is displayed as its values are
+**/
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultSet {
+    pub columns: Vec<String>,
+    pub formats: Vec<ColumnFormat>,
+    pub rows: Vec<Vec<CoreValue>>,
+}
+
+/**
+ * How the values of a column are displayed, which is kept from the file column it was selected from whatever it is named
+**/
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ColumnFormat {
+    #[default]
+    Plain,
+    /* The Mode column is an integer that is displayed in octal such as 0755 */
+    Octal,
+}
+
+impl ResultSet {
+    /**
+     * Writes a value of the i-th column for display, timestamps are written in the given time zone
+    **/
+    pub fn display_value(&self, i: usize, value: &CoreValue, time_zone: TimeZoneSetting) -> String {
+        match (self.formats.get(i), value) {
+            (Some(ColumnFormat::Octal), CoreValue::Integer(integer)) => { format!("{:04o}", integer) }
+            _ => { value.display(time_zone) }
+        }
+    }
+
+    /**
+     * Displays the result set as a table with its timestamps written in the given time zone
+    **/
+    pub fn display(&self, time_zone: TimeZoneSetting) -> DisplayResultSet<'_> {
+        DisplayResultSet { result_set: self, time_zone }
+    }
+}
+
+/**
+ * A result set that is displayed as a table, created by ResultSet::display
+**/
+pub struct DisplayResultSet<'a> {
+    result_set: &'a ResultSet,
+    time_zone: TimeZoneSetting,
+}
+
+impl fmt::Display for DisplayResultSet<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let result_set = self.result_set;
+        let rows: Vec<Vec<String>> = result_set.rows.iter().map(|row| row.iter().enumerate().map(|(i, value)| result_set.display_value(i, value, self.time_zone)).collect()).collect();
+
+        let mut widths: Vec<usize> = result_set.columns.iter().map(|column| column.chars().count()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let write_row = |f: &mut fmt::Formatter, cells: &[String]| -> fmt::Result {
+            let cells: Vec<String> = cells.iter().zip(&widths).map(|(cell, width)| format!("{:width$}", cell, width = width)).collect();
+            writeln!(f, "{}", cells.join(" | ").trim_end())
+        };
+
+        write_row(f, &result_set.columns)?;
+        writeln!(f, "{}", widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<String>>().join("-+-"))?;
+        for row in &rows {
+            write_row(f, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::*;
+
+    #[test]
+    fn display_result_set() {
+        let result_set = ResultSet {
+            columns: vec!["Name".to_owned(), "Size".to_owned()],
+            formats: Vec::new(),
+            rows: vec![
+                vec![CoreValue::String("Cargo.toml".to_owned()), CoreValue::Integer(512)],
+                vec![CoreValue::Path(PathBuf::from("src")), CoreValue::Null],
+            ],
+        };
+
+        assert_eq!(result_set.display(TimeZoneSetting::Utc).to_string(), "Name       | Size\n-----------+-----\nCargo.toml | 512\nsrc        | NULL\n");
+    }
+
+    #[test]
+    fn display_mode() {
+        let result_set = ResultSet {
+            columns: vec!["m".to_owned(), "Mode & 2".to_owned()],
+            formats: vec![ColumnFormat::Octal, ColumnFormat::Plain],
+            rows: vec![vec![CoreValue::Integer(0o755), CoreValue::Integer(0o755 & 2)]],
+        };
+
+        assert_eq!(result_set.display(TimeZoneSetting::Utc).to_string(), "m    | Mode & 2\n-----+---------\n0755 | 0\n");
+    }
+}
@@ -0,0 +1,283 @@
+use std::{path::PathBuf, rc::Rc, str::FromStr};
+use strum::AsStaticRef;
+use super::{column::*, error::CoreError, result_set::ColumnFormat, value::CoreValue};
+
+/**
+ * A row of the tables being queried, each table's columns are kept under the table's name or alias
+ *
+ * A table without a matching row in an outer join has no columns, so each of its columns is NULL.
+ * A row of a correlated subquery keeps the row of the enclosing query, whose columns are found when they are not in this row.
+ * The columns a USING or NATURAL join matched on can be used without a table name, their value is the first table's that is not NULL
+**/
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Row {
+    pub tables: Vec<(/*table name*/ String, TableRow)>,
+    pub outer: Option<Rc<Row>>,
+    pub join_columns: Option<Rc<[String]>>,
+}
+
+/**
+ * The columns of a single table in a row
+ *
+ * A directory's files have the file columns, a common table expression has the columns of its select list
+**/
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableRow {
+    Files(Vec<FileColumn>),
+    Values(/*column names*/ Rc<[String]>, Vec<CoreValue>),
+}
+
+/**
+ * The name and columns of a table in the FROM clause, a directory has no column names as it has every file column
+ *
+ * The root is the directory that was queried, the relative paths and depths of the table's files are below it
+**/
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSchema {
+    pub name: String,
+    pub columns: Option<Rc<[String]>>,
+    /* The formats of the named columns, a directory's columns have the format of their file column */
+    pub formats: Rc<[ColumnFormat]>,
+    pub root: Option<PathBuf>,
+}
+
+impl Row {
+    pub fn new(table_name: &str, columns: Vec<FileColumn>) -> Self {
+        Row { tables: vec![(table_name.to_owned(), TableRow::Files(columns))], outer: None, join_columns: None }
+    }
+
+    /**
+     * The row of a table that is not a directory, such as a common table expression
+    **/
+    pub fn values(table_name: &str, columns: Rc<[String]>, values: Vec<CoreValue>) -> Self {
+        Row { tables: vec![(table_name.to_owned(), TableRow::Values(columns, values))], outer: None, join_columns: None }
+    }
+
+    /**
+     * The row of a directory that had no match in an outer join
+    **/
+    pub fn null(table_name: &str) -> Self {
+        Row::new(table_name, Vec::new())
+    }
+
+    /**
+     * Sets the row of the enclosing query, for a row of a subquery
+    **/
+    pub fn with_outer(mut self, outer: Option<Rc<Row>>) -> Self {
+        self.outer = outer;
+        self
+    }
+
+    /**
+     * Sets the columns a USING or NATURAL join matched on, keeping those of earlier joins
+    **/
+    pub fn with_join_columns(mut self, join_columns: &Rc<[String]>) -> Self {
+        self.join_columns = match self.join_columns.take() {
+            Some(columns) => { Some(columns.iter().chain(join_columns.iter()).cloned().collect()) }
+            None => { Some(join_columns.clone()) }
+        };
+        self
+    }
+
+    /**
+     * Appends the tables of another row to the end of this row
+    **/
+    pub fn join(mut self, other: Row) -> Self {
+        self.tables.extend(other.tables);
+        if let Some(join_columns) = &other.join_columns {
+            self = self.with_join_columns(join_columns);
+        }
+        self
+    }
+
+    fn is_join_column(&self, column_name: &str) -> bool {
+        self.join_columns.iter().flat_map(|columns| columns.iter()).any(|name| name.eq_ignore_ascii_case(column_name))
+    }
+
+    /**
+     * Finds a column by name, the column must be qualified by its table when more than one table of the row has it
+     *
+     * Columns are looked for in this row before the row of the enclosing query
+    **/
+    pub fn column(&self, table_name: Option<&str>, column_name: &str) -> Result<CoreValue, CoreError> {
+        let table_row = match table_name {
+            Some(table_name) => {
+                match (self.tables.iter().find(|(name, _)| name.eq_ignore_ascii_case(table_name)), &self.outer) {
+                    (Some((_, table_row)), _) => { table_row }
+                    (None, Some(outer)) => { return outer.column(Some(table_name), column_name) }
+                    (None, None) => { return Err(CoreError::GeneralError(format!("The multi-part identifier '{}.{}' could not be bound", table_name, column_name))) }
+                }
+            }
+            None => {
+                let mut table_rows = self.tables.iter().map(|(_, table_row)| table_row).filter(|table_row| table_row.has_column(column_name));
+                match (table_rows.next(), table_rows.next(), &self.outer) {
+                    (Some(_), Some(_), _) if self.is_join_column(column_name) => {
+                        for (_, table_row) in &self.tables {
+                            match table_row.column(column_name) {
+                                Ok(CoreValue::Null) | Err(_) => {}
+                                value => { return value }
+                            }
+                        }
+                        return Ok(CoreValue::Null)
+                    }
+                    (Some(_), Some(_), _) => { return Err(CoreError::GeneralError(format!("Ambiguous column name '{}'", column_name))) }
+                    (Some(table_row), None, _) => { table_row }
+                    (None, _, Some(outer)) => { return outer.column(None, column_name) }
+                    (None, _, None) if self.tables.is_empty() => { FileColumn::from_str(column_name)?; return Ok(CoreValue::Null) }
+                    (None, _, None) => { return Err(CoreError::GeneralError(format!("Invalid column name '{}'", column_name))) }
+                }
+            }
+        };
+
+        table_row.column(column_name)
+    }
+}
+
+impl TableRow {
+    /**
+     * Whether the table has the column, a file has every file column even when it is NULL
+    **/
+    pub fn has_column(&self, column_name: &str) -> bool {
+        match self {
+            TableRow::Files(_) => { FileColumn::from_str(column_name).is_ok() }
+            TableRow::Values(names, _) => { names.iter().any(|name| name.eq_ignore_ascii_case(column_name)) }
+        }
+    }
+
+    pub fn column(&self, column_name: &str) -> Result<CoreValue, CoreError> {
+        match self {
+            TableRow::Files(columns) => { Ok(CoreValue::from(columns.column(&FileColumn::from_str(column_name)?))) }
+            TableRow::Values(names, values) => {
+                match names.iter().position(|name| name.eq_ignore_ascii_case(column_name)) {
+                    Some(i) => { Ok(values.get(i).cloned().unwrap_or(CoreValue::Null)) }
+                    None => { Err(CoreError::GeneralError(format!("Invalid column name '{}'", column_name))) }
+                }
+            }
+        }
+    }
+}
+
+impl TableSchema {
+    /**
+     * A directory, which has every file column
+    **/
+    pub fn files(name: &str) -> Self {
+        TableSchema { name: name.to_owned(), columns: None, formats: Rc::from(Vec::new()), root: None }
+    }
+
+    pub fn column_names(&self) -> Vec<String> {
+        match &self.columns {
+            Some(columns) => { columns.to_vec() }
+            None => { FileColumn::iterator().map(|column| column.as_static().to_owned()).collect() }
+        }
+    }
+
+    /**
+     * Whether the table has the column, a directory has the hash columns too although they are not in its column names
+    **/
+    pub fn has_column(&self, column_name: &str) -> bool {
+        match &self.columns {
+            Some(columns) => { columns.iter().any(|name| name.eq_ignore_ascii_case(column_name)) }
+            None => { FileColumn::from_str(column_name).is_ok() }
+        }
+    }
+
+    /**
+     * The format the column is displayed in, the Mode of a file is displayed in octal
+    **/
+    pub fn column_format(&self, column_name: &str) -> ColumnFormat {
+        match &self.columns {
+            Some(columns) => { columns.iter().position(|name| name.eq_ignore_ascii_case(column_name)).and_then(|i| self.formats.get(i).copied()).unwrap_or_default() }
+            None => {
+                match FileColumn::from_str(column_name) {
+                    Ok(FileColumn::Mode(_)) => { ColumnFormat::Octal }
+                    _ => { ColumnFormat::Plain }
+                }
+            }
+        }
+    }
+
+    /**
+     * The row of this table when it had no match in an outer join
+    **/
+    pub fn null_row(&self) -> Row {
+        match &self.columns {
+            Some(columns) => { Row::values(&self.name, columns.clone(), Vec::new()) }
+            None => { Row::null(&self.name) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+    use super::*;
+
+    #[test]
+    fn column_qualified() {
+        let row = Row::new("currdir", vec![FileColumn::Name(Some(OsString::from("a")))])
+            .join(Row::null("prevdir"));
+
+        assert_eq!(row.column(Some("CurrDir"), "name"), Ok(CoreValue::String("a".to_owned())));
+        assert_eq!(row.column(Some("prevdir"), "Name"), Ok(CoreValue::Null));
+        assert!(row.column(Some("otherdir"), "Name").is_err());
+        assert!(row.column(None, "Name").is_err());
+    }
+
+    #[test]
+    fn column_outer() {
+        let outer = Row::new("f", vec![FileColumn::Name(Some(OsString::from("a")))]);
+        let row = Row::new("d", vec![FileColumn::Name(Some(OsString::from("b")))]).with_outer(Some(Rc::new(outer)));
+
+        assert_eq!(row.column(Some("f"), "Name"), Ok(CoreValue::String("a".to_owned())));
+        assert_eq!(row.column(None, "Name"), Ok(CoreValue::String("b".to_owned())));
+        assert!(row.column(Some("g"), "Name").is_err());
+    }
+
+    #[test]
+    fn column_unqualified() {
+        let a = Row::values("a", Rc::from(vec!["x".to_owned()]), vec![CoreValue::Integer(1)]);
+        let b = Row::values("b", Rc::from(vec!["y".to_owned()]), vec![CoreValue::Integer(2)]);
+        let row = a.clone().join(b);
+
+        assert_eq!(row.column(None, "X"), Ok(CoreValue::Integer(1)));
+        assert_eq!(row.column(None, "y"), Ok(CoreValue::Integer(2)));
+        assert!(row.column(None, "z").is_err());
+
+        let row = a.clone().join(Row::new("d", vec![FileColumn::Name(Some(OsString::from("d")))]));
+        assert_eq!(row.column(None, "x"), Ok(CoreValue::Integer(1)));
+        assert_eq!(row.column(None, "Name"), Ok(CoreValue::String("d".to_owned())));
+        assert!(row.column(None, "z").is_err());
+
+        let row = a.clone().join(Row::values("c", Rc::from(vec!["X".to_owned()]), vec![CoreValue::Integer(3)]));
+        assert!(row.column(None, "x").is_err());
+    }
+
+    #[test]
+    fn column_values() {
+        let schema = TableSchema { name: "big".to_owned(), columns: Some(Rc::from(vec!["kb".to_owned()])), formats: Rc::from(vec![ColumnFormat::Octal]), root: None };
+        let row = Row::values("big", schema.columns.clone().expect("Columns are named"), vec![CoreValue::Integer(4)]);
+
+        assert_eq!(row.column(Some("big"), "KB"), Ok(CoreValue::Integer(4)));
+        assert!(row.column(None, "Name").is_err());
+        assert_eq!(schema.null_row().column(None, "kb"), Ok(CoreValue::Null));
+        assert_eq!(schema.column_names(), vec!["kb"]);
+        assert!(schema.has_column("KB"));
+        assert!(!schema.has_column("Name"));
+        assert!(TableSchema::files("dir").has_column("Hash_Sha256"));
+        assert_eq!(schema.column_format("KB"), ColumnFormat::Octal);
+        assert_eq!(TableSchema::files("dir").column_format("mode"), ColumnFormat::Octal);
+        assert_eq!(TableSchema::files("dir").column_format("Size"), ColumnFormat::Plain);
+    }
+
+    #[test]
+    fn column_join_columns() {
+        let row = Row::null("a")
+            .join(Row::new("b", vec![FileColumn::Name(Some(OsString::from("b")))]))
+            .with_join_columns(&Rc::from(vec!["name".to_owned()]));
+
+        assert_eq!(row.column(None, "Name"), Ok(CoreValue::String("b".to_owned())));
+        assert_eq!(row.column(Some("a"), "Name"), Ok(CoreValue::Null));
+        assert!(row.column(None, "Size").is_err());
+    }
+}
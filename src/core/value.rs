@@ -0,0 +1,368 @@
+use std::{cmp::Ordering, ffi::{OsStr, OsString}, fmt, hash::{Hash, Hasher}, mem::discriminant, path::PathBuf, time::{Duration, SystemTime}};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use sqlparser::ast::{self, DateTimeField};
+use super::error::CoreError;
+
+/**
+ * The time zone timestamps are written in, and that dates and times without an offset are read in
+**/
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TimeZoneSetting {
+    /* Timestamps are in UTC unless SET TIME ZONE LOCAL is run before the query */
+    #[default]
+    Utc,
+    Local,
+}
+
+/**
+ * A typed value produced while evaluating a query
+ *
+ * Integers and floats are coerced to each other, and strings holding a number are coerced to a number when compared with one
+**/
+#[derive(Debug, Clone)]
+pub enum CoreValue {
+    Null,
+    Integer(i64),
+    Float(f64),
+    String(String),
+    /* A name that is not valid UTF-8 */
+    Bytes(OsString),
+    Boolean(bool),
+    Timestamp(SystemTime),
+    Duration(Duration),
+    Path(PathBuf),
+}
+
+impl CoreValue {
+    /**
+     * Converts a literal written in the SQL into a value
+    **/
+    pub fn from_literal(value: &ast::Value) -> Result<CoreValue, CoreError> {
+        match value {
+            ast::Value::Number(number, _) => {
+                match number.parse::<i64>() {
+                    Ok(integer) => { Ok(CoreValue::Integer(integer)) }
+                    Err(_) => { number.parse::<f64>().map(CoreValue::Float).map_err(|_| CoreError::GeneralError(format!("The number {} is invalid", number))) }
+                }
+            }
+            ast::Value::SingleQuotedString(str) | ast::Value::NationalStringLiteral(str) | ast::Value::EscapedStringLiteral(str) | ast::Value::UnicodeStringLiteral(str) => { Ok(CoreValue::String(str.clone())) }
+            ast::Value::HexStringLiteral(hex) => {
+                let bytes = (0..hex.len()).step_by(2).map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+                    .collect::<Option<Vec<u8>>>()
+                    .ok_or_else(|| CoreError::GeneralError(format!("The hex string {} is invalid", value)))?;
+
+                Ok(bytes_value(bytes))
+            }
+            ast::Value::Boolean(b) => { Ok(CoreValue::Boolean(*b)) }
+            ast::Value::Null => { Ok(CoreValue::Null) }
+            _ => { Err(CoreError::GeneralError(format!("The value {} is not supported", value))) }
+        }
+    }
+
+    /**
+     * Converts an amount of a unit of time, such as INTERVAL '1' DAY, into a duration
+    **/
+    pub fn from_interval(amount: &CoreValue, leading_field: Option<&DateTimeField>) -> Result<CoreValue, CoreError> {
+        let seconds_per_unit = match leading_field {
+            Some(DateTimeField::Week(_)) => { 604_800.0 }
+            Some(DateTimeField::Day) => { 86_400.0 }
+            Some(DateTimeField::Hour) => { 3_600.0 }
+            Some(DateTimeField::Minute) => { 60.0 }
+            Some(DateTimeField::Second) | None => { 1.0 }
+            Some(field) => { return Err(CoreError::GeneralError(format!("The interval unit {} is not supported, use WEEK, DAY, HOUR, MINUTE or SECOND", field))) }
+        };
+
+        match amount.to_f64() {
+            Some(amount) if amount >= 0.0 && amount.is_finite() => { Ok(CoreValue::Duration(Duration::from_secs_f64(amount * seconds_per_unit))) }
+            _ => { Err(CoreError::GeneralError(format!("The interval {} must be a positive number", amount))) }
+        }
+    }
+
+    /**
+     * Reads a number from a numeric value or a string containing a number
+    **/
+    pub fn to_f64(&self) -> Option<f64> {
+        match self {
+            CoreValue::Integer(integer) => { Some(*integer as f64) }
+            CoreValue::Float(float) => { Some(*float) }
+            CoreValue::String(str) => { str.trim().parse::<f64>().ok() }
+            _ => { None }
+        }
+    }
+
+    /**
+     * Reads a whole number from a numeric value or a string containing a number
+    **/
+    pub fn to_i64(&self) -> Option<i64> {
+        match self {
+            CoreValue::Integer(integer) => { Some(*integer) }
+            CoreValue::Float(float) if float.fract() == 0.0 && float.abs() < i64::MAX as f64 => { Some(*float as i64) }
+            CoreValue::String(str) => { str.trim().parse::<i64>().ok() }
+            _ => { None }
+        }
+    }
+
+    /**
+     * The raw bytes of a string, name or path
+    **/
+    pub fn to_bytes(&self) -> Option<&[u8]> {
+        match self {
+            CoreValue::String(str) => { Some(str.as_bytes()) }
+            CoreValue::Bytes(bytes) => { Some(bytes.as_encoded_bytes()) }
+            CoreValue::Path(path) => { Some(path.as_os_str().as_encoded_bytes()) }
+            _ => { None }
+        }
+    }
+
+    /**
+     * The path held by a path, name or string
+    **/
+    pub fn to_path(&self) -> Option<PathBuf> {
+        match self {
+            CoreValue::Path(path) => { Some(path.clone()) }
+            CoreValue::String(str) => { Some(PathBuf::from(str)) }
+            CoreValue::Bytes(bytes) => { Some(PathBuf::from(bytes)) }
+            _ => { None }
+        }
+    }
+
+    /**
+     * The time held by a timestamp or a string containing a date, such as '2026-01-01' or '2026-01-01 12:30:00'
+     *
+     * A date or time without an offset is in the given time zone
+    **/
+    pub fn to_timestamp(&self, time_zone: TimeZoneSetting) -> Option<SystemTime> {
+        match self {
+            CoreValue::Timestamp(timestamp) => { Some(*timestamp) }
+            CoreValue::String(str) => { parse_timestamp(str.trim(), time_zone) }
+            _ => { None }
+        }
+    }
+
+    /**
+     * The kind of value an equality can hash on, values of the same kind are equal when their key values are
+    **/
+    pub fn key_kind(&self) -> std::mem::Discriminant<CoreValue> {
+        match self {
+            CoreValue::Float(_) => { discriminant(&CoreValue::Integer(0)) }
+            CoreValue::Bytes(_) | CoreValue::Path(_) => { discriminant(&CoreValue::String(String::new())) }
+            value => { discriminant(value) }
+        }
+    }
+
+    /**
+     * The value an equality hashes on, a whole float is an integer and a string, name or path is its bytes
+    **/
+    pub fn key_value(self) -> CoreValue {
+        match self {
+            CoreValue::Float(float) if float.fract() == 0.0 && float.abs() < i64::MAX as f64 => { CoreValue::Integer(float as i64) }
+            CoreValue::String(str) => { CoreValue::Bytes(OsString::from(str)) }
+            CoreValue::Path(path) => { CoreValue::Bytes(path.into_os_string()) }
+            value => { value }
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, CoreValue::Null)
+    }
+
+    /**
+     * Formats a value for output like Display does, but with timestamps written in the given time zone
+    **/
+    pub fn display(&self, time_zone: TimeZoneSetting) -> String {
+        match self {
+            CoreValue::Timestamp(timestamp) => { format_timestamp(*timestamp, time_zone) }
+            value => { value.to_string() }
+        }
+    }
+}
+
+fn parse_timestamp(str: &str, time_zone: TimeZoneSetting) -> Option<SystemTime> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(str) {
+        return Some(timestamp.into());
+    }
+
+    let naive = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"].iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(str, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(str, "%Y-%m-%d").ok().and_then(|date| date.and_hms_opt(0, 0, 0)))?;
+
+    match time_zone {
+        TimeZoneSetting::Utc => { Some(Utc.from_utc_datetime(&naive).into()) }
+        TimeZoneSetting::Local => { Local.from_local_datetime(&naive).earliest().map(SystemTime::from) }
+    }
+}
+
+/**
+ * Writes a timestamp as ISO-8601 to the second, UTC is written with a Z rather than an offset
+**/
+fn format_timestamp(timestamp: SystemTime, time_zone: TimeZoneSetting) -> String {
+    match time_zone {
+        TimeZoneSetting::Utc => { DateTime::<Utc>::from(timestamp).to_rfc3339_opts(SecondsFormat::Secs, true) }
+        TimeZoneSetting::Local => { DateTime::<Local>::from(timestamp).to_rfc3339_opts(SecondsFormat::Secs, true) }
+    }
+}
+
+/**
+ * Keeps bytes that are not valid UTF-8 as they are, rather than replacing them
+**/
+#[cfg(unix)]
+pub fn bytes_value(bytes: Vec<u8>) -> CoreValue {
+    use std::os::unix::ffi::OsStringExt;
+    match String::from_utf8(bytes) {
+        Ok(str) => { CoreValue::String(str) }
+        Err(e) => { CoreValue::Bytes(OsString::from_vec(e.into_bytes())) }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn bytes_value(bytes: Vec<u8>) -> CoreValue {
+    CoreValue::String(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/**
+ * Names are kept as strings unless they are not valid UTF-8
+**/
+pub fn os_str_value(str: &OsStr) -> CoreValue {
+    match str.to_str() {
+        Some(str) => { CoreValue::String(str.to_owned()) }
+        None => { CoreValue::Bytes(str.to_owned()) }
+    }
+}
+
+/**
+ * Values are equal when they are the same type and hold the same value, floats are equal when their bits are
+**/
+impl PartialEq for CoreValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CoreValue::Float(a), CoreValue::Float(b)) => { a.to_bits() == b.to_bits() }
+            (CoreValue::Integer(a), CoreValue::Integer(b)) => { a == b }
+            (CoreValue::String(a), CoreValue::String(b)) => { a == b }
+            (CoreValue::Bytes(a), CoreValue::Bytes(b)) => { a == b }
+            (CoreValue::Boolean(a), CoreValue::Boolean(b)) => { a == b }
+            (CoreValue::Timestamp(a), CoreValue::Timestamp(b)) => { a == b }
+            (CoreValue::Duration(a), CoreValue::Duration(b)) => { a == b }
+            (CoreValue::Path(a), CoreValue::Path(b)) => { a == b }
+            (CoreValue::Null, CoreValue::Null) => { true }
+            _ => { false }
+        }
+    }
+}
+
+impl Eq for CoreValue {}
+
+impl Hash for CoreValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        discriminant(self).hash(state);
+        match self {
+            CoreValue::Null => {}
+            CoreValue::Integer(integer) => { integer.hash(state) }
+            CoreValue::Float(float) => { float.to_bits().hash(state) }
+            CoreValue::String(str) => { str.hash(state) }
+            CoreValue::Bytes(bytes) => { bytes.hash(state) }
+            CoreValue::Boolean(b) => { b.hash(state) }
+            CoreValue::Timestamp(timestamp) => { timestamp.hash(state) }
+            CoreValue::Duration(duration) => { duration.hash(state) }
+            CoreValue::Path(path) => { path.hash(state) }
+        }
+    }
+}
+
+/**
+ * Formats a value for output, strings are written without their quotes and timestamps as ISO-8601 to the second in UTC
+**/
+impl fmt::Display for CoreValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CoreValue::Null => { write!(f, "NULL") }
+            CoreValue::Integer(integer) => { write!(f, "{}", integer) }
+            CoreValue::Float(float) => { write!(f, "{}", float) }
+            CoreValue::String(str) => { write!(f, "{}", str) }
+            CoreValue::Bytes(bytes) => { write!(f, "{}", bytes.to_string_lossy()) }
+            CoreValue::Boolean(b) => { write!(f, "{}", b) }
+            CoreValue::Timestamp(timestamp) => { write!(f, "{}", format_timestamp(*timestamp, TimeZoneSetting::Utc)) }
+            CoreValue::Duration(duration) => { write!(f, "PT{}S", duration.as_secs_f64()) }
+            CoreValue::Path(path) => { write!(f, "{}", path.to_string_lossy()) }
+        }
+    }
+}
+
+/**
+ * Compares two values, None is returned if either value is NULL
+ *
+ * A string compared to a timestamp is read as a date or time in the given time zone
+**/
+pub fn compare_values(left: &CoreValue, right: &CoreValue, time_zone: TimeZoneSetting) -> Result<Option<Ordering>, CoreError> {
+    match (left, right) {
+        (CoreValue::Null, _) | (_, CoreValue::Null) => { Ok(None) }
+        (CoreValue::Integer(a), CoreValue::Integer(b)) => { Ok(Some(a.cmp(b))) }
+        (CoreValue::Integer(_), _) | (_, CoreValue::Integer(_)) | (CoreValue::Float(_), _) | (_, CoreValue::Float(_)) => {
+            match (left.to_f64(), right.to_f64()) {
+                (Some(a), Some(b)) => { Ok(a.partial_cmp(&b)) }
+                _ => { Err(invalid_types()) }
+            }
+        }
+        (CoreValue::Boolean(a), CoreValue::Boolean(b)) => { Ok(Some(a.cmp(b))) }
+        (CoreValue::Timestamp(_), _) | (_, CoreValue::Timestamp(_)) => {
+            match (left.to_timestamp(time_zone), right.to_timestamp(time_zone)) {
+                (Some(a), Some(b)) => { Ok(Some(a.cmp(&b))) }
+                _ => { Err(invalid_types()) }
+            }
+        }
+        (CoreValue::Duration(a), CoreValue::Duration(b)) => { Ok(Some(a.cmp(b))) }
+        _ => {
+            match (left.to_bytes(), right.to_bytes()) {
+                (Some(a), Some(b)) => { Ok(Some(a.cmp(b))) }
+                _ => { Err(invalid_types()) }
+            }
+        }
+    }
+}
+
+pub fn invalid_types() -> CoreError {
+    CoreError::GeneralError("The data types are invalid for the specified operator".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_values_coercion() {
+        assert_eq!(compare_values(&CoreValue::Integer(3_000_000_000), &CoreValue::Float(2.5e9), TimeZoneSetting::Utc), Ok(Some(Ordering::Greater)));
+        assert_eq!(compare_values(&CoreValue::Integer(10), &CoreValue::String("9".to_owned()), TimeZoneSetting::Utc), Ok(Some(Ordering::Greater)));
+        assert_eq!(compare_values(&CoreValue::Path(PathBuf::from("./b")), &CoreValue::String("./a".to_owned()), TimeZoneSetting::Utc), Ok(Some(Ordering::Greater)));
+        assert_eq!(compare_values(&CoreValue::Null, &CoreValue::Integer(1), TimeZoneSetting::Utc), Ok(None));
+        assert!(compare_values(&CoreValue::Boolean(true), &CoreValue::Integer(1), TimeZoneSetting::Utc).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn compare_values_bytes() {
+        let name = CoreValue::from_literal(&ast::Value::HexStringLiteral("66ff".to_owned())).expect("Hex string is valid");
+
+        assert!(matches!(name, CoreValue::Bytes(_)));
+        assert_eq!(compare_values(&name, &CoreValue::String("f".to_owned()), TimeZoneSetting::Utc), Ok(Some(Ordering::Greater)));
+        assert_eq!(CoreValue::from_literal(&ast::Value::HexStringLiteral("61".to_owned())), Ok(CoreValue::String("a".to_owned())));
+    }
+
+    #[test]
+    fn compare_values_timestamps() {
+        let day = CoreValue::Timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(86_400));
+
+        assert_eq!(compare_values(&day, &CoreValue::String("1970-01-02".to_owned()), TimeZoneSetting::Utc), Ok(Some(Ordering::Equal)));
+        assert_eq!(compare_values(&day, &CoreValue::String("1970-01-01 23:59:59".to_owned()), TimeZoneSetting::Utc), Ok(Some(Ordering::Greater)));
+        assert_eq!(compare_values(&CoreValue::String("1970-01-02T01:00:00+02:00".to_owned()), &day, TimeZoneSetting::Utc), Ok(Some(Ordering::Less)));
+        assert!(compare_values(&day, &CoreValue::String("yesterday".to_owned()), TimeZoneSetting::Utc).is_err());
+        assert!(compare_values(&day, &CoreValue::Integer(1), TimeZoneSetting::Utc).is_err());
+    }
+
+    #[test]
+    fn display_values() {
+        assert_eq!(CoreValue::Timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(86_400)).to_string(), "1970-01-02T00:00:00Z");
+        assert_eq!(CoreValue::Timestamp(SystemTime::UNIX_EPOCH + Duration::from_millis(1_500)).to_string(), "1970-01-01T00:00:01Z");
+        assert_eq!(CoreValue::Timestamp(SystemTime::UNIX_EPOCH).display(TimeZoneSetting::Utc), "1970-01-01T00:00:00Z");
+        assert_eq!(CoreValue::Float(0.5).to_string(), "0.5");
+        assert_eq!(CoreValue::Null.to_string(), "NULL");
+    }
+}
@@ -2,31 +2,28 @@
 use sqlparser::ast::*;
 use std::{fs::File, io::Write, fs, io::Error};
 use std::fs::DirEntry;
-use sqlparser::ast::Expr::Value;
 use std::env;
 use std::vec;
 use std::path::Path;
-use crate::core::{column::*, error::CoreError, file::*};
+use crate::core::{column::*, error::CoreError, file::*, result_set::ResultSet, value::{CoreValue, TimeZoneSetting}};
 use serde_json::{json, to_string};
 use strum::AsStaticRef;
 
-pub fn write_csv(columns: Vec<FileColumn>, files: Vec<Vec<FileColumn>>, file_path: &String) -> Result<(), CoreError> {
-    // FIXME! Ordering of columns and values     
+/**
+ * Writes the result set to a CSV file, the column names are written as the header record
+ *
+ * Timestamps are written in the given time zone
+**/
+pub fn write_csv(result_set: &ResultSet, file_path: &str, time_zone: TimeZoneSetting) -> Result<(), CoreError> {
     let mut wtr = csv::Writer::from_path(file_path)?;
-                    
-    wtr.write_record(columns.iter().map(|c| c.as_static()))?;
 
-    for rows in files {
-        let mut cells: Vec<String> = Vec::new();
-        for col in rows {
-            cells.push(col.to_string());
-        }
+    wtr.write_record(&result_set.columns)?;
 
-        while cells.len() < columns.len() {
-            cells.push(String::from(""));
-        }
-        
-        wtr.write_record(cells)?;
+    for row in &result_set.rows {
+        wtr.write_record(row.iter().enumerate().map(|(i, value)| match value {
+            CoreValue::Null => { String::new() }
+            _ => { result_set.display_value(i, value, time_zone) }
+        }))?;
     }
 
     wtr.flush()?;
@@ -34,34 +31,85 @@ pub fn write_csv(columns: Vec<FileColumn>, files: Vec<Vec<FileColumn>>, file_pat
     Ok(())
 }
 
-pub fn write_json(columns: Vec<FileColumn>, files: Vec<Vec<FileColumn>>, file_path: &String)  -> Result<(), CoreError> {
-    let json = json!(files);
+/**
+ * Writes the result set to a JSON file as an array of objects keyed by column name
+ * 
+ * Each column must have a different name, such as a.Name and b.Name of a join, or one would overwrite the other
+**/
+pub fn write_json(result_set: &ResultSet, file_path: &str, time_zone: TimeZoneSetting) -> Result<(), CoreError> {
+    for (i, column) in result_set.columns.iter().enumerate() {
+        if result_set.columns[..i].iter().any(|other| other.eq_ignore_ascii_case(column)) {
+            return Err(CoreError::GeneralError(format!("The column name '{}' is used more than once, use aliases to give each column a different name before inserting into a JSON file", column)));
+        }
+    }
+
+    let rows: Vec<serde_json::Map<String, serde_json::Value>> = result_set.rows.iter().map(|row|
+        result_set.columns.iter().cloned().zip(row.iter().map(|value| value_to_json(value, time_zone))).collect()
+    ).collect();
 
-    // TODO! Write json to a file
-    print!("{}", json);
+    let json = serde_json::to_string_pretty(&rows).map_err(|e| CoreError::IOError(e.to_string()))?;
+    fs::write(file_path, json)?;
 
     Ok(())
 }
 
+/**
+ * Numbers and booleans keep their JSON types, every other value is written as it is displayed
+**/
+fn value_to_json(value: &CoreValue, time_zone: TimeZoneSetting) -> serde_json::Value {
+    match value {
+        CoreValue::Integer(integer) => { json!(integer) }
+        CoreValue::Float(float) => { json!(float) }
+        CoreValue::Boolean(boolean) => { json!(boolean) }
+        CoreValue::Null => { serde_json::Value::Null }
+        _ => { json!(value.display(time_zone)) }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn result_set() -> ResultSet {
+        ResultSet {
+            columns: vec!["file".to_owned(), "kb".to_owned()],
+            formats: Vec::new(),
+            rows: vec![
+                vec![CoreValue::String("a.txt".to_owned()), CoreValue::Integer(4)],
+                vec![CoreValue::String("b".to_owned()), CoreValue::Null],
+            ],
+        }
+    }
+
     #[test]
     fn write_csv() {
-        // let output_path = "./temp.csv";
-        // File::create(&output_path).expect("Failure writing temp file");
-        // let files = fs::read_dir(env::current_dir().expect("Failure reading current directory"))
-        //                         .expect("Failure reading current directory")
-        //                         .into_iter().map(|f|
-        //                             CoreFile::from(f.unwrap())
-        //                         ).collect();
-        
-        // super::write_csv(files, &output_path.to_string());
-
-        // let csv = fs::metadata(output_path).expect("Output file should exist");
-        // assert_eq!(csv.len() > 8, true); 
+        let output_path = env::temp_dir().join("systemsql_write_csv.csv");
+
+        super::write_csv(&result_set(), output_path.to_str().unwrap(), TimeZoneSetting::Utc).expect("Failure writing temp file");
+
+        let csv = fs::read_to_string(&output_path).expect("Output file should exist");
+        fs::remove_file(&output_path).ok();
+        assert_eq!(csv, "file,kb\na.txt,4\nb,\n");
     }
-}
 
+    #[test]
+    fn write_json() {
+        let output_path = env::temp_dir().join("systemsql_write_json.json");
+
+        super::write_json(&result_set(), output_path.to_str().unwrap(), TimeZoneSetting::Utc).expect("Failure writing temp file");
 
+        let json = fs::read_to_string(&output_path).expect("Output file should exist");
+        fs::remove_file(&output_path).ok();
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&json).unwrap(), json!([{ "file": "a.txt", "kb": 4 }, { "file": "b", "kb": null }]));
+        assert!(json.find("\"file\"") < json.find("\"kb\""));
+    }
+
+    #[test]
+    fn write_json_duplicate_columns() {
+        let output_path = env::temp_dir().join("systemsql_write_json_duplicate_columns.json");
+        let result_set = ResultSet { columns: vec!["Name".to_owned(), "Name".to_owned()], formats: Vec::new(), rows: vec![vec![CoreValue::String("a".to_owned()), CoreValue::String("b".to_owned())]] };
+
+        assert!(super::write_json(&result_set, output_path.to_str().unwrap(), TimeZoneSetting::Utc).is_err());
+        assert!(!output_path.exists());
+    }
+}
@@ -11,18 +11,16 @@ pub fn inner_join<TLeft, TRight, TKey, TResult>(
     result_selector: Box<dyn Fn(TLeft, TRight) -> TResult>) -> Vec<TResult> 
     where TKey: std::cmp::Eq + std::hash::Hash + std::fmt::Debug, TRight: Clone + std::fmt::Debug, TLeft: Clone, TResult: std::fmt::Debug 
 {
-    let mut right_iter = right.iter();
-
     let mut results = Vec::new();
     let mut lookup: HashMap<TKey, Vec<TLeft>> = HashMap::new();
     for item in left
     {
         let key = left_key_selector(&item);
-        let values = lookup.entry(key).or_insert(Vec::new());
+        let values = lookup.entry(key).or_default();
         values.push(item);
     }
-    while let Some(right_item) = right_iter.next() {
-        let lefts: Option<&Vec<TLeft>> = lookup.get(&right_key_selector(&right_item));
+    for right_item in right.iter() {
+        let lefts: Option<&Vec<TLeft>> = lookup.get(&right_key_selector(right_item));
         if let Some(lefts) = lefts {
             for left in lefts
             {
@@ -45,18 +43,16 @@ pub fn left_join<TLeft, TRight, TKey, TResult>(
     result_selector: Box<dyn Fn(TLeft, Option<TRight>) -> TResult>) -> Vec<TResult> 
     where TKey: std::cmp::Eq + std::hash::Hash + std::fmt::Debug, TRight: Clone + std::fmt::Debug, TLeft: Clone, TResult: std::fmt::Debug 
 {
-    let mut left_iter = left.iter();
-
     let mut results = Vec::new();
     let mut lookup: HashMap<TKey, Vec<TRight>> = HashMap::new();
     for item in right
     {
         let key = right_key_selector(&item);
-        let values = lookup.entry(key).or_insert(Vec::new());
+        let values = lookup.entry(key).or_default();
         values.push(item);
     }
-    while let Some(left_item) = left_iter.next() {
-        let rights: Option<&Vec<TRight>> = lookup.get(&left_key_selector(&left_item));
+    for left_item in left.iter() {
+        let rights: Option<&Vec<TRight>> = lookup.get(&left_key_selector(left_item));
         if let Some(rights) = rights {
             for right in rights
             {
@@ -70,6 +66,102 @@ pub fn left_join<TLeft, TRight, TKey, TResult>(
     results
 }
 
+/**
+* Will join the matching elements from the first vector with all elements from the second vector based on a matching key
+*/
+pub fn right_join<TLeft, TRight, TKey, TResult>(
+    left: Vec<TLeft>, 
+    right: Vec<TRight>, 
+    left_key_selector: Box<dyn Fn(&TLeft) -> TKey>, 
+    right_key_selector: Box<dyn Fn(&TRight) -> TKey>, 
+    result_selector: Box<dyn Fn(Option<TLeft>, TRight) -> TResult>) -> Vec<TResult> 
+    where TKey: std::cmp::Eq + std::hash::Hash + std::fmt::Debug, TRight: Clone + std::fmt::Debug, TLeft: Clone, TResult: std::fmt::Debug 
+{
+    let mut results = Vec::new();
+    let mut lookup: HashMap<TKey, Vec<TLeft>> = HashMap::new();
+    for item in left
+    {
+        let key = left_key_selector(&item);
+        let values = lookup.entry(key).or_default();
+        values.push(item);
+    }
+    for right_item in right.iter() {
+        let lefts: Option<&Vec<TLeft>> = lookup.get(&right_key_selector(right_item));
+        if let Some(lefts) = lefts {
+            for left in lefts
+            {
+                results.push(result_selector(Some(left.clone()), right_item.clone()));
+            }
+        } else {
+            results.push(result_selector(None, right_item.clone()));
+        }
+    }
+
+    results
+}
+
+/**
+* Will join all elements from the first vector with all elements from the second vector based on a matching key
+* 
+* Elements of the second vector without a match are joined after the elements of the first vector
+*/
+pub fn full_join<TLeft, TRight, TKey, TResult>(
+    left: Vec<TLeft>, 
+    right: Vec<TRight>, 
+    left_key_selector: Box<dyn Fn(&TLeft) -> TKey>, 
+    right_key_selector: Box<dyn Fn(&TRight) -> TKey>, 
+    result_selector: Box<dyn Fn(Option<TLeft>, Option<TRight>) -> TResult>) -> Vec<TResult> 
+    where TKey: std::cmp::Eq + std::hash::Hash + std::fmt::Debug, TRight: Clone + std::fmt::Debug, TLeft: Clone, TResult: std::fmt::Debug 
+{
+    let mut results = Vec::new();
+    let mut matched = vec![false; right.len()];
+    let mut lookup: HashMap<TKey, Vec<usize>> = HashMap::new();
+    for (i, item) in right.iter().enumerate()
+    {
+        let key = right_key_selector(item);
+        let values = lookup.entry(key).or_default();
+        values.push(i);
+    }
+    for left_item in left.iter() {
+        let rights: Option<&Vec<usize>> = lookup.get(&left_key_selector(left_item));
+        if let Some(rights) = rights {
+            for i in rights
+            {
+                matched[*i] = true;
+                results.push(result_selector(Some(left_item.clone()), Some(right[*i].clone())));
+            }
+        } else {
+            results.push(result_selector(Some(left_item.clone()), None));
+        }
+    }
+    for (right_item, matched) in right.into_iter().zip(matched) {
+        if !matched {
+            results.push(result_selector(None, Some(right_item)));
+        }
+    }
+
+    results
+}
+
+/**
+* Will join every element of the first vector with every element of the second vector
+*/
+pub fn cross_join<TLeft, TRight, TResult>(
+    left: Vec<TLeft>, 
+    right: Vec<TRight>, 
+    result_selector: Box<dyn Fn(TLeft, TRight) -> TResult>) -> Vec<TResult> 
+    where TRight: Clone, TLeft: Clone 
+{
+    let mut results = Vec::new();
+    for left_item in left.iter() {
+        for right_item in right.iter() {
+            results.push(result_selector(left_item.clone(), right_item.clone()));
+        }
+    }
+
+    results
+}
+
 #[cfg(test)]
 mod tests {
     use std::{ffi::OsString, path::PathBuf};
@@ -81,12 +173,12 @@ mod tests {
 
     #[test]
     fn test_inner_join() {
-        let left_one = CoreFile { name: Some(OsString::from("left_one")), file_extension: None, file_type: Some(FileType::File), path: None };
-        let left_two = CoreFile { name: Some(OsString::from("left_two")), file_extension: None, file_type: Some(FileType::File), path: None };
+        let left_one = CoreFile { name: Some(OsString::from("left_one")), file_extension: None, file_type: Some(FileType::File), path: None, root: None };
+        let left_two = CoreFile { name: Some(OsString::from("left_two")), file_extension: None, file_type: Some(FileType::File), path: None, root: None };
         let left = vec![left_one.clone(), left_two.clone()];
 
-        let right_one = CoreFile { name: Some(OsString::from("right_one")), file_extension: None, file_type: Some(FileType::Dir), path: None };
-        let right_two = CoreFile { name: Some(OsString::from("right_two")), file_extension: None, file_type: Some(FileType::File), path: None };
+        let right_one = CoreFile { name: Some(OsString::from("right_one")), file_extension: None, file_type: Some(FileType::Dir), path: None, root: None };
+        let right_two = CoreFile { name: Some(OsString::from("right_two")), file_extension: None, file_type: Some(FileType::File), path: None, root: None };
         let right = vec![right_one.clone(), right_two.clone()];
 
         let result = inner_join(left, right, Box::new(|l| l.file_type.clone()), Box::new(|r| r.file_type.clone()), Box::new(|l, r| vec![l, r]));
@@ -96,16 +188,52 @@ mod tests {
 
     #[test]
     fn test_left_outer_join() {
-        let left_one = CoreFile { name: Some(OsString::from("left_one")), file_extension: None, file_type: Some(FileType::File), path: None };
-        let left_two = CoreFile { name: Some(OsString::from("left_two")), file_extension: None, file_type: Some(FileType::File), path: None };
+        let left_one = CoreFile { name: Some(OsString::from("left_one")), file_extension: None, file_type: Some(FileType::File), path: None, root: None };
+        let left_two = CoreFile { name: Some(OsString::from("left_two")), file_extension: None, file_type: Some(FileType::File), path: None, root: None };
         let left = vec![left_one.clone(), left_two.clone()];
 
-        let right_one = CoreFile { name: Some(OsString::from("right_one")), file_extension: None, file_type: Some(FileType::Dir), path: None };
-        let right_two = CoreFile { name: Some(OsString::from("right_two")), file_extension: None, file_type: Some(FileType::Dir), path: None };
+        let right_one = CoreFile { name: Some(OsString::from("right_one")), file_extension: None, file_type: Some(FileType::Dir), path: None, root: None };
+        let right_two = CoreFile { name: Some(OsString::from("right_two")), file_extension: None, file_type: Some(FileType::Dir), path: None, root: None };
         let right = vec![right_one.clone(), right_two.clone()];
 
         let result = left_join(left, right, Box::new(|l| l.file_type.clone()), Box::new(|r| r.file_type.clone()), Box::new(|l: CoreFile, r: Option<CoreFile>| vec![Some(l), r]));
 
         assert_eq!(result, vec![vec![Some(left_one), None], vec![Some(left_two), None]]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_right_outer_join() {
+        let left_one = CoreFile { name: Some(OsString::from("left_one")), file_extension: None, file_type: Some(FileType::File), path: None, root: None };
+        let left = vec![left_one.clone()];
+
+        let right_one = CoreFile { name: Some(OsString::from("right_one")), file_extension: None, file_type: Some(FileType::Dir), path: None, root: None };
+        let right_two = CoreFile { name: Some(OsString::from("right_two")), file_extension: None, file_type: Some(FileType::File), path: None, root: None };
+        let right = vec![right_one.clone(), right_two.clone()];
+
+        let result = right_join(left, right, Box::new(|l| l.file_type.clone()), Box::new(|r| r.file_type.clone()), Box::new(|l: Option<CoreFile>, r: CoreFile| vec![l, Some(r)]));
+
+        assert_eq!(result, vec![vec![None, Some(right_one)], vec![Some(left_one), Some(right_two)]]);
+    }
+
+    #[test]
+    fn test_full_outer_join() {
+        let left_one = CoreFile { name: Some(OsString::from("left_one")), file_extension: None, file_type: Some(FileType::File), path: None, root: None };
+        let left_two = CoreFile { name: Some(OsString::from("left_two")), file_extension: None, file_type: None, path: None, root: None };
+        let left = vec![left_one.clone(), left_two.clone()];
+
+        let right_one = CoreFile { name: Some(OsString::from("right_one")), file_extension: None, file_type: Some(FileType::Dir), path: None, root: None };
+        let right_two = CoreFile { name: Some(OsString::from("right_two")), file_extension: None, file_type: Some(FileType::File), path: None, root: None };
+        let right = vec![right_one.clone(), right_two.clone()];
+
+        let result = full_join(left, right, Box::new(|l| l.file_type.clone()), Box::new(|r| r.file_type.clone()), Box::new(|l: Option<CoreFile>, r: Option<CoreFile>| vec![l, r]));
+
+        assert_eq!(result, vec![vec![Some(left_one), Some(right_two)], vec![Some(left_two), None], vec![None, Some(right_one)]]);
+    }
+
+    #[test]
+    fn test_cross_join() {
+        let result = cross_join(vec![1, 2], vec!['a', 'b'], Box::new(|l, r| (l, r)));
+
+        assert_eq!(result, vec![(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]);
+    }
+}
@@ -32,7 +32,7 @@ fn main() {
         CoreDialect { dialect: Box::new(sqlparser::dialect::MsSqlDialect {}) 
     });
 
-    std::process::exit(match parse_sql(&sql, dialect) {
+    std::process::exit(match parse_sql(sql, dialect) {
         Ok(_) => {
             0
         }
@@ -1,11 +1,9 @@
 
 use sqlparser::{ast::*, dialect::MsSqlDialect, parser::Parser, test_utils};
-use std::{collections::HashMap, ffi::OsString, fmt::{self, Display}, fs::{self, DirEntry}, str::FromStr, result};
-use crate::{core::{column::*, file::*, dialect, error::CoreError, expr_result::ExprResult}, enumerable};
+use std::{cmp::Ordering, collections::{HashMap, HashSet}, ffi::OsString, fmt::{self, Display}, fs::{self, DirEntry}, path::PathBuf, rc::Rc, str::FromStr, result};
+use crate::{core::{aggregate::Aggregate, column::*, file::*, dialect, error::CoreError, hash, pattern::Pattern, result_set::{ColumnFormat, ResultSet}, row::{Row, TableSchema}, value::*}, enumerable};
 use crate::display::*;
-use strum::IntoEnumIterator;
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use strum::{AsStaticRef, IntoEnumIterator};
 
 /**
  * Parses and evaluates SQL string
@@ -13,15 +11,14 @@ use std::hash::{Hash, Hasher};
  * The SQL will be executed against the system's files and directories 
 **/
 pub fn parse_sql(sql: &str, dialect: dialect::CoreDialect) -> Result<(), CoreError> {
-    let dialect = dialect.dialect;
-
-    let parse_result = Parser::parse_sql(&*dialect, &sql)?;
+    let parse_result = Parser::parse_sql(&dialect, sql)?;
 
     //println!("Raw SQL:\n'{}'", sql);
     //println!("Parse results:\n{:#?}", parse_result);
 
+    let mut options = QueryOptions { like_bracket_classes: dialect.has_like_bracket_classes(), ..QueryOptions::default() };
     for statement in parse_result {
-        consume_statement(statement)?;
+        consume_statement(statement, &mut options)?;
     }
 
     Ok(())
@@ -29,13 +26,18 @@ pub fn parse_sql(sql: &str, dialect: dialect::CoreDialect) -> Result<(), CoreErr
 
 /**
  * Consumes and executes a SQL statement
+ * 
+ * The options are those set by the statements before it, such as by SET MAXRECURSION.
+ * The file hashes of the statements before it are forgotten, so a file that has changed is hashed again
 **/
-pub fn consume_statement(statement: Statement) -> Result<(), CoreError> {
+pub fn consume_statement(statement: Statement, options: &mut QueryOptions) -> Result<(), CoreError> {
+    hash::clear_cache();
+
     match statement {
         // SELECT columns FROM table_name ...
         Statement::Query(query) => {
-            let query = consume_query(*query)?;
-            println!("{:#?}", query);
+            let result_set = consume_query(*query, &QueryContext::new(options.clone()))?;
+            print!("{}", result_set.display(options.time_zone));
 
             Ok(())
         }
@@ -43,457 +45,2429 @@ pub fn consume_statement(statement: Statement) -> Result<(), CoreError> {
         // table_name is the file being inserted into
         // columns are the columns to insert defined in paranthesis after the table_name (simple idents)
         // source is the query to pull data for the insert from
-        Statement::Insert { table_name, columns, source } => {
-            println!("Source: {:#?}", source);
-            
-            let mut files = consume_query(*source)?;
-
-            println!("Columns are {:#?}", columns);
-            println!("Files:\n{:#?}", files);
-
-            //let columns: Vec<String> = if columns.len() > 0 {
-            //    columns.into_iter().map(|c|
-            //        FileColumn::from_str(c.value.as_str()).expect(format!("No such column {} was found", c.value).as_str())
-            //    ).collect()
-            //} else {
-            //    FileColumn::iter().collect()
-            //};
-
-            //let column_cmp = |a: &String, b: &String| std::mem::discriminant(a) == std::mem::discriminant(b);
-            //files = files.into_iter().map(|row| row.into_iter().filter(|column|
-            //    columns.iter().any(|c| column_cmp(c, column))).collect::<Vec<String>>()
-            //).collect::<Vec<Vec<String>>>();
-            
-            //if let Some(table_name) = table_name.0.first() {
-            //    match table_name.value.chars().rev().take_while(|c| *c != '.').collect::<String>().as_str() {
-                    // ...mmh
-            //        "vsc" => { write_csv(columns, files, &table_name.value)? }
-            //        "nosj" => { write_json(columns, files, &table_name.value)? }
-            //        _ => { println!("Files:\n{:#?}", files); }
-            //    }
-            //};
+        Statement::Insert(Insert { table_name, columns, source, .. }) => {
+            let source = source.ok_or_else(|| CoreError::GeneralError("INSERT requires a query to select the rows to insert".to_owned()))?;
+            let mut result_set = consume_query(*source, &QueryContext::new(options.clone()))?;
+
+            // The insert columns name the selected columns by position
+            if !columns.is_empty() {
+                if columns.len() != result_set.columns.len() {
+                    return Err(CoreError::GeneralError("The select list for the INSERT statement contains a different number of items than the insert list".to_owned()));
+                }
 
-            Ok(())
+                result_set.columns = columns.into_iter().map(|column| column.value).collect();
+            }
+
+            let file_path = table_name.0.iter().map(|ident| ident.value.as_str()).collect::<Vec<&str>>().join(".");
+            let file_path = file_path.as_str();
+            match file_path.rsplit('.').next().map(|extension| extension.to_lowercase()).as_deref() {
+                Some("csv") => { write_csv(&result_set, file_path, options.time_zone) }
+                Some("json") => { write_json(&result_set, file_path, options.time_zone) }
+                _ => { Err(CoreError::GeneralError(format!("Cannot insert into {}, only .csv and .json files are supported", file_path))) }
+            }
         }
-        // UPDATE table_name SET column = value ...
-        Statement::Update { table_name, assignments, selection } => { 
-            let table_name = &table_name.0[0].value;
-            let files = consume_table_name(table_name)?;
+        // UPDATE table_name SET Name = value WHERE ...
+        // renames the files of the directory the WHERE clause is true for
+        Statement::Update { table, assignments, from, selection, .. } => {
+            if from.is_some() || !table.joins.is_empty() {
+                return Err(CoreError::GeneralError("UPDATE of a join is not supported, only the files of a single directory can be updated".to_owned()));
+            }
 
-            if let Some(expr) = selection {
-                let mut hash_map: HashMap<String, Vec<CoreFile>> = [(table_name.clone(), files)].iter().cloned().collect();
-                let result = consume_expr(expr.clone(), Some(&mut hash_map))?;
+            let context = QueryContext::new(options.clone());
+            let schema = relation_schema(&table.relation, &[], &context)?;
+            if schema.columns.is_some() || is_lateral(&table.relation) {
+                return Err(CoreError::GeneralError(format!("Cannot update {}, only directories can be updated", table.relation)));
             }
 
-            for assignment in &assignments {
-                
+            let rows = scan_relation(&table.relation, &schema, selection.as_ref(), None, &context)?;
+            update_rows(&rows, &assignments, &context)
+        }
+        // SET TIME ZONE 'UTC' or SET TIME ZONE LOCAL
+        // sets the time zone timestamps are written in for the statements after it
+        Statement::SetTimeZone { value, .. } => {
+            let time_zone = match &value {
+                Expr::Value(Value::SingleQuotedString(name)) => { name.clone() }
+                Expr::Identifier(ident) => { ident.value.clone() }
+                _ => { return Err(CoreError::GeneralError(format!("The time zone {} is not supported, use UTC or LOCAL", value))) }
+            };
+
+            match &time_zone.to_ascii_uppercase()[..] {
+                "UTC" => { options.time_zone = TimeZoneSetting::Utc }
+                "LOCAL" => { options.time_zone = TimeZoneSetting::Local }
+                _ => { return Err(CoreError::GeneralError(format!("The time zone '{}' is not supported, use UTC or LOCAL", time_zone))) }
             }
 
             Ok(())
         }
+        // SET MAXRECURSION = n
+        // sets the number of times a recursive common table expression can repeat for the statements after it, 0 has no limit
+        Statement::SetVariable { variables: OneOrManyWithParens::One(name), value, .. } if name.to_string().eq_ignore_ascii_case("MAXRECURSION") => {
+            options.max_recursion = match value.as_slice() {
+                [Expr::Value(Value::Number(number, _))] => { number.parse::<usize>().map_err(|_| CoreError::GeneralError(format!("MAXRECURSION must be a positive whole number, not {}", number)))? }
+                _ => { return Err(CoreError::GeneralError(format!("MAXRECURSION must be a positive whole number, not {}", display_comma_separated(&value)))) }
+            };
+
+            Ok(())
+        }
         // SHOW COLUMNS FROM table_name
-        Statement::ShowColumns { extended, full, table_name, filter } => { 
-            let columns = FileColumn::iterator().map(|c| c.to_string()).collect::<Vec<String>>();
+        // the hash columns are listed last as SELECT * leaves them out, they read each file's contents so must be selected by name
+        Statement::ShowColumns { .. } => { 
+            let columns = FileColumn::iterator().cloned().chain(FileColumn::iter().filter(|column| column.hash_algorithm().is_some()))
+                .map(|column| column.as_static()).collect::<Vec<&str>>();
             println!("Columns: {}", columns.join(", "));
 
             Ok(())
         }
-        _ => { unimplemented!() }
+        statement => { Err(CoreError::GeneralError(format!("The statement {} is not supported", statement))) }
+    }
+}
+
+/**
+ * Renames the files of the rows to the names the assignments give them
+ *
+ * Every new name is worked out before any file is renamed, so a name that is invalid or already taken leaves every file as it was
+**/
+fn update_rows(rows: &[Row], assignments: &[Assignment], context: &QueryContext) -> Result<(), CoreError> {
+    let name_expr = match assignments {
+        [Assignment { target: AssignmentTarget::ColumnName(column), value }] if column.to_string().eq_ignore_ascii_case("Name") => { value }
+        _ => { return Err(CoreError::GeneralError(format!("Cannot SET {}, only the Name of a file can be updated", display_comma_separated(assignments)))) }
+    };
+
+    let mut renames = Vec::new();
+    for row in rows {
+        let path = row.column(None, "Path")?.to_path().ok_or_else(|| CoreError::GeneralError("The file to rename has no path".to_owned()))?;
+        let name = evaluate_expr(name_expr, Scope::Row(row), context)?.to_path()
+            .filter(|name| matches!(name.components().collect::<Vec<_>>()[..], [std::path::Component::Normal(_)]))
+            .ok_or_else(|| CoreError::GeneralError(format!("The name given to {} must be a file name without a directory", path.display())))?;
+
+        let new_path = path.with_file_name(name);
+        if new_path != path && (new_path.symlink_metadata().is_ok() || renames.iter().any(|(_, other)| *other == new_path)) {
+            return Err(CoreError::GeneralError(format!("Cannot rename {} to {}, a file of that name already exists", path.display(), new_path.display())));
+        }
+        renames.push((path, new_path));
+    }
+
+    for (path, new_path) in renames {
+        fs::rename(&path, &new_path)?;
     }
+
+    Ok(())
 }
 
 /**
  * Consumes and executes a SQL query
+ * 
+ * A subquery is executed in the context of the query it is in, so it can use its common table expressions and reference the columns of its row
 **/
-pub fn consume_query(query: Query) -> Result<Vec<(String, String, String)>, CoreError> {
-    match query.body {
+pub fn consume_query(query: Query, context: &QueryContext) -> Result<ResultSet, CoreError> {
+    let context = &match &query.with {
+        Some(with) => { consume_with(with, context)? }
+        None => { context.clone() }
+    };
+
+    let row_limit = RowLimit::from_query(query.limit.as_ref(), query.offset.as_ref(), query.fetch.as_ref(), context)?;
+    let order_by = query.order_by.as_ref().map(|order_by| &order_by.exprs[..]).unwrap_or(&[]);
+    match *query.body {
         SetExpr::Select(select) => { 
-            let files = consume_select(*select)?;
+            let files = consume_select(*select, order_by, row_limit, context)?;
 
-            return Ok(files);
+            Ok(files)
+        }
+        body => {
+            if row_limit.with_ties && order_by.is_empty() {
+                return Err(CoreError::GeneralError("WITH TIES requires an ORDER BY clause".to_owned()));
+            }
+
+            let result_set = consume_set_expr(body, context)?;
+            let order_indexes = order_by.iter().map(|order| result_set_column_index(&result_set, &order.expr)).collect::<Result<Vec<usize>, CoreError>>()?;
+            let mut keyed_rows: Vec<KeyedRow> = result_set.rows.into_iter().map(|row| (order_indexes.iter().map(|i| row[*i].clone()).collect(), row)).collect();
+            if !order_by.is_empty() {
+                keyed_rows = order_rows(keyed_rows, order_by, context.options.time_zone)?;
+            }
+
+            Ok(ResultSet { columns: result_set.columns, formats: result_set.formats, rows: limit_rows(keyed_rows, &row_limit, order_by, context.options.time_zone).into_iter().map(|(_, row)| row).collect() })
         }
-        _ => { unimplemented!() }
     }
 }
 
 /**
- * Consumes and executes a SQL select statement
+ * Options that apply to each query of the statements after they are set
 **/
-fn consume_select(select: Select) -> Result<Vec<(String, String, String)>, CoreError> {
-    let mut hash_map: HashMap</*table name*/ String, /*row*/ Vec<CoreFile>> = HashMap::new();
-    let mut result_columns: /*rows*/ Vec</*columns*/ Vec<FileColumn>> = Vec::new();
-    let select_projection = select.projection;
-
-    if !select.from.is_empty() {
-        for table_with_join in select.from {
-            // Load the table's files into memory
-            let (table_name, files) = consume_relation(table_with_join.relation)?;
-
-            hash_map.insert(table_name, files.clone());
-            //println!("hash map{:#?}", hash_map);
-
-            result_columns = hash_map.clone().into_iter().flat_map(|f| 
-                f.1.clone().iter().map(|cf| cf.columns()).collect::<Vec<Vec<FileColumn>>>()
-            ).collect::<Vec<Vec<FileColumn>>>();
-            //println!("{:#?}", result_columns);
-
-            for join in table_with_join.joins {
-                let (join_table_name, join_files) = consume_relation(join.relation.clone())?;
-                match join.join_operator {
-                    JoinOperator::Inner(constraint) => {
-                        match constraint {
-                            sqlparser::ast::JoinConstraint::On(expr) => {
-                                hash_map.insert(join_table_name, join_files.clone());
-
-                                let expr_result = consume_expr(expr, Some(&mut hash_map))?;
-
-                                //println!("expr_result: {:#?}", expr_result);
-
-                                match expr_result {
-                                    ExprResult::BinaryOp((left_table_name, left), op, (right_table_name, right)) => {
-                                        let join_result = enumerable::inner_join(files.clone(), join_files, left, right, Box::new(|f: CoreFile, jf: CoreFile| vec![f, jf]));
-                                        hash_map.remove(&left_table_name);
-                                        hash_map.remove(&right_table_name);
-
-                                        result_columns = join_result.iter().map(|f| 
-                                            f.clone().iter().flat_map(|cf| cf.columns()).collect()
-                                        ).collect();
-                                    }
-                                    _ => { unimplemented!() }
-                                }
-                            }
-                            _ => { unimplemented!() }
-                        }
-                    }
-                    JoinOperator::LeftOuter(constraint) => {
-                        match constraint {
-                            sqlparser::ast::JoinConstraint::On(expr) => {
-                                hash_map.insert(join_table_name, join_files.clone());
-
-                                let expr_result = consume_expr(expr, Some(&mut hash_map))?;
-
-                                //println!("expr_result: {:#?}", expr_result);
-
-                                match expr_result {
-                                    ExprResult::BinaryOp((left_table_name, left), op, (right_table_name, right)) => {
-                                        let join_result = enumerable::left_join(files.clone(), join_files, left, right, Box::new(|f: CoreFile, jf: Option<CoreFile>| vec![Some(f), jf]));
-                                        
-                                        hash_map.remove(&left_table_name);
-                                        hash_map.remove(&right_table_name);
-
-                                        result_columns = join_result.iter().map(|f| 
-                                            f.iter().flat_map(|cf|
-                                                match cf {
-                                                    Some(cf) => { cf.columns() }
-                                                    None => { vec![FileColumn::Null] }
-                                                } 
-                                            ).collect()
-                                        ).collect();
-                                    }
-                                    _ => { unimplemented!() }
-                                }
-                            }
-                            _ => { unimplemented!() }
-                        }
-                    }
-                    _ => { unimplemented!() }
-                }
-            }
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryOptions {
+    /* The number of times a recursive common table expression can be repeated, as a guard against infinite recursion, 0 has no limit */
+    pub max_recursion: usize,
+    /* Whether [abc] in a LIKE pattern is a set of characters, which depends on the dialect the SQL was parsed with */
+    pub like_bracket_classes: bool,
+    /* The time zone timestamps are written in, and that dates and times without an offset are compared in, set by SET TIME ZONE */
+    pub time_zone: TimeZoneSetting,
+}
 
-            if select.distinct {
-                todo!()
-                //result_columns.sort();
-                //result_columns.dedup()
-            }
-
-            if let Some(ref top) = select.top  {
-                if let Expr::Value(ref value) = top.quantity.as_ref().unwrap() {
-                    let quantity: Option<usize> = match value {
-                        Value::Number(str) => { str.parse().ok() }
-                        Value::SingleQuotedString(str) => { str.parse().ok() }
-                        Value::NationalStringLiteral(str) => { str.parse().ok() }
-                        Value::HexStringLiteral(str) => { str.parse().ok() }
-                        Value::Boolean(b) => { Some(*b as usize) }
-                        Value::Interval { value, leading_field, leading_precision, last_field, fractional_seconds_precision } => { None }
-                        Value::Null => { None }
-                    };
-
-                    result_columns = if let Some(quantity) = quantity {
-                        result_columns.into_iter().take(quantity).collect()
-                    } else {
-                        result_columns
-                    }
-                }
+impl Default for QueryOptions {
+    fn default() -> Self {
+        QueryOptions { max_recursion: 100, like_bracket_classes: true, time_zone: TimeZoneSetting::Utc }
+    }
+}
+
+/**
+ * The state a query is executed in, which a subquery inherits from the query it is in
+**/
+#[derive(Debug, Clone, Default)]
+pub struct QueryContext {
+    options: QueryOptions,
+    /* The row of the enclosing query when the query is a subquery, its columns can be referenced by a correlated subquery */
+    outer: Option<Rc<Row>>,
+    /* The common table expressions that are in scope, with the innermost last, and the directory each read its files from */
+    ctes: Vec<(String, Rc<ResultSet>, Option<PathBuf>)>,
+}
+
+impl QueryContext {
+    pub fn new(options: QueryOptions) -> Self {
+        QueryContext { options, outer: None, ctes: Vec::new() }
+    }
+
+    /**
+     * The context of a subquery executed for the row
+    **/
+    fn with_outer(&self, outer: Option<Rc<Row>>) -> Self {
+        QueryContext { outer, ..self.clone() }
+    }
+
+    /**
+     * The context with the common table expression in scope, it hides any expression of the same name already in scope
+     *
+     * The root is the directory the expression read its files from, so that a directory read from one of its paths keeps the depth below it
+    **/
+    fn with_cte(&self, name: &str, result_set: ResultSet, root: Option<PathBuf>) -> Self {
+        let mut context = self.clone();
+        context.ctes.push((name.to_owned(), Rc::new(result_set), root));
+        context
+    }
+
+    /**
+     * Finds the innermost common table expression with the name
+    **/
+    fn find_cte(&self, name: &str) -> Option<Rc<ResultSet>> {
+        self.find_cte_with_root(name).map(|(result_set, _)| result_set)
+    }
+
+    fn find_cte_with_root(&self, name: &str) -> Option<(Rc<ResultSet>, Option<PathBuf>)> {
+        self.ctes.iter().rev().find(|(cte_name, _, _)| cte_name.eq_ignore_ascii_case(name)).map(|(_, result_set, root)| (result_set.clone(), root.clone()))
+    }
+
+    /**
+     * An empty row of the enclosing query, which the rows read by the query are joined to
+    **/
+    fn outer_row(&self) -> Row {
+        Row::default().with_outer(self.outer.clone())
+    }
+}
+
+/**
+ * Executes the common table expressions of a WITH clause, each can be used as a table by the query and the expressions after it
+**/
+fn consume_with(with: &With, context: &QueryContext) -> Result<QueryContext, CoreError> {
+    let mut context = context.clone();
+    for cte in &with.cte_tables {
+        let name = &cte.alias.name.value;
+        let root = set_expr_root(&cte.query.body, &context);
+        let result_set = match &*cte.query.body {
+            SetExpr::SetOperation { op: SetOperator::Union, set_quantifier, left, right } if with.recursive && set_expr_references_table(right, name) => {
+                consume_recursive_cte(cte, left, right, *set_quantifier == SetQuantifier::All, root.clone(), &context)?
             }
+            _ => { name_cte_columns(cte, consume_query((*cte.query).clone(), &context)?)? }
+        };
+
+        context = context.with_cte(name, result_set, root);
+    }
+
+    Ok(context)
+}
+
+/**
+ * Executes a recursive common table expression
+ * 
+ * The anchor query is run first, then the recursive query is run against the rows returned last until it returns no new rows
+**/
+fn consume_recursive_cte(cte: &Cte, anchor: &SetExpr, recursive: &SetExpr, is_all: bool, root: Option<PathBuf>, context: &QueryContext) -> Result<ResultSet, CoreError> {
+    let name = &cte.alias.name.value;
+    let mut result_set = name_cte_columns(cte, consume_set_expr(anchor.clone(), context)?)?;
+    let mut seen = HashSet::new();
+    if !is_all {
+        result_set.rows.retain(|row| seen.insert(row.clone()));
+    }
 
-            println!("results: {:#?}", result_columns);
-            println!("projections: {:#?}", select_projection);
+    let max_recursion = context.options.max_recursion;
+    let mut working_rows = result_set.rows.clone();
+    for level in 0.. {
+        if working_rows.is_empty() {
+            break;
+        } else if max_recursion > 0 && level == max_recursion {
+            return Err(CoreError::GeneralError(format!("The maximum recursion {} has been exhausted before the common table expression '{}' completed, use SET MAXRECURSION = n to raise the limit or 0 to remove it", max_recursion, name)));
+        }
 
-            // This is a very quick and dirty implmentation down for limiting the data set to our selected columns
-            // It will fail on joined tables or compound identifiers
-            return Ok(select_projection.iter().flat_map(|select_expr|
-                result_columns.iter().filter_map(|row|
-                    // col = Name(Some("Cargo.lock",),)
+        let recursive_context = context.with_cte(name, ResultSet { columns: result_set.columns.clone(), formats: result_set.formats.clone(), rows: working_rows }, root.clone());
+        let recursive_result = consume_set_expr(recursive.clone(), &recursive_context)?;
 
-                    match_select_item(row.clone(), select_expr)
-                ).flatten().collect::<Vec<(String, String, String)>>()
-                
-                // row = [Name(Some("Cargo.lock",),),Path(Some("./Cargo.lock",),),Type(Some(File,),),FileExtension(Some("lock",),),Size(Some(5500,),),Path(Some("./Cargo.lock",),),Created(Some(SystemTime{intervals: 132534735133613130,},),),],
-                //row.into_iter().filter_map(|col| 
-                    // col = Name(Some("Cargo.lock",),)
-                    //select_projection.iter().any(|p | { match_select_item(col.clone(), p) })
-                //).collect::<Vec<String>>()
-            ).collect::<Vec<(String, String, String)>>());        
+        if recursive_result.columns.len() != result_set.columns.len() {
+            return Err(CoreError::GeneralError(format!("The anchor and recursive queries of the common table expression '{}' must select the same number of columns", name)));
         }
-    } else {
-        //select_projection.
+
+        working_rows = recursive_result.rows;
+        if !is_all {
+            working_rows.retain(|row| seen.insert(row.clone()));
+        }
+
+        result_set.rows.extend(working_rows.iter().cloned());
     }
 
-    for group in select.group_by {
-        todo!()
+    Ok(result_set)
+}
+
+/**
+ * Names the columns of a common table expression by its column list, when it has one
+**/
+fn name_cte_columns(cte: &Cte, mut result_set: ResultSet) -> Result<ResultSet, CoreError> {
+    if !cte.alias.columns.is_empty() {
+        if cte.alias.columns.len() != result_set.columns.len() {
+            return Err(CoreError::GeneralError(format!("'{}' has {} columns but {} were specified in its column list", cte.alias.name.value, result_set.columns.len(), cte.alias.columns.len())));
+        }
+
+        result_set.columns = cte.alias.columns.iter().map(|column| column.name.value.clone()).collect();
     }
 
-    if let Some(seelction) = select.selection {
-        todo!()
+    Ok(result_set)
+}
+
+/**
+ * The directory the files of a query are read from, which is the root of the first table of its first select
+ *
+ * The anchor query of a recursive common table expression is first, so its directory is the root of the whole walk
+**/
+fn set_expr_root(set_expr: &SetExpr, context: &QueryContext) -> Option<PathBuf> {
+    match set_expr {
+        SetExpr::Select(select) => { select.from.first().and_then(|table_with_joins| relation_schema(&table_with_joins.relation, &[], context).ok()).and_then(|schema| schema.root) }
+        SetExpr::Query(query) => { set_expr_root(&query.body, context) }
+        SetExpr::SetOperation { left, .. } => { set_expr_root(left, context) }
+        _ => { None }
     }
+}
 
-    if let Some(having) = select.having {
-        todo!()
+/**
+ * Whether a table in the FROM clause of the query has the name
+**/
+fn set_expr_references_table(set_expr: &SetExpr, table_name: &str) -> bool {
+    match set_expr {
+        SetExpr::Select(select) => {
+            select.from.iter()
+                .flat_map(|table_with_joins| std::iter::once(&table_with_joins.relation).chain(table_with_joins.joins.iter().map(|join| &join.relation)))
+                .any(|relation| matches!(relation, TableFactor::Table { name, .. } if name.0.len() == 1 && name.0[0].value.eq_ignore_ascii_case(table_name)))
+        }
+        SetExpr::Query(query) => { set_expr_references_table(&query.body, table_name) }
+        SetExpr::SetOperation { left, right, .. } => { set_expr_references_table(left, table_name) || set_expr_references_table(right, table_name) }
+        _ => { false }
     }
+}
 
-    return Ok(result_columns.into_iter().flat_map(|row| 
-        row.into_iter().map(|items|
-            (calculate_hash(&items).to_string(), "".to_owned(), items.to_string())
-        ).collect::<Vec<(String, String, String)>>()
-    ).collect::<Vec<(String, String, String)>>());
+/**
+ * Consumes and executes the body of a query, such as a select or the set operation of two selects
+ * 
+ * The rows are not ordered or limited, that is done by the query the body belongs to
+**/
+fn consume_set_expr(set_expr: SetExpr, context: &QueryContext) -> Result<ResultSet, CoreError> {
+    match set_expr {
+        SetExpr::Select(select) => { consume_select(*select, &[], RowLimit::default(), context) }
+        SetExpr::Query(query) => { consume_query(*query, context) }
+        SetExpr::SetOperation { op, set_quantifier, left, right } => {
+            let left = consume_set_expr(*left, context)?;
+            let right = consume_set_expr(*right, context)?;
+            if left.columns.len() != right.columns.len() {
+                return Err(CoreError::GeneralError(format!("All queries combined using a {} operator must have an equal number of expressions in their select lists", op)));
+            }
+
+            let (mut left_rows, mut right_rows) = (left.rows, right.rows);
+            coerce_set_operation_rows(&mut left_rows, &mut right_rows, &op)?;
+            let is_all = match set_quantifier {
+                SetQuantifier::All => { true }
+                SetQuantifier::Distinct | SetQuantifier::None => { false }
+                set_quantifier => { return Err(CoreError::GeneralError(format!("{} {} is not supported", op, set_quantifier))) }
+            };
+
+            Ok(ResultSet { columns: left.columns, formats: left.formats, rows: combine_rows(&op, is_all, left_rows, right_rows) })
+        }
+        set_expr => { Err(CoreError::GeneralError(format!("The query {} is not supported", set_expr))) }
+    }
 }
 
-fn unbox<T>(value: Box<T>) -> T {
-    *value
+/**
+ * UNION keeps the rows of both queries, INTERSECT the rows in both and EXCEPT the rows of the left query not in the right
+ * 
+ * Duplicate rows are removed unless ALL is used, then each row of the right query matches a single row of the left query
+**/
+fn combine_rows(op: &SetOperator, is_all: bool, left_rows: Vec<Vec<CoreValue>>, right_rows: Vec<Vec<CoreValue>>) -> Vec<Vec<CoreValue>> {
+    match op {
+        SetOperator::Union if is_all => { left_rows.into_iter().chain(right_rows).collect() }
+        SetOperator::Union => { distinct_rows(left_rows.into_iter().chain(right_rows)) }
+        SetOperator::Intersect | SetOperator::Except => {
+            let mut right_counts: HashMap<Vec<CoreValue>, usize> = HashMap::new();
+            for row in right_rows {
+                *right_counts.entry(row).or_default() += 1;
+            }
+
+            let is_intersect = *op == SetOperator::Intersect;
+            let left_rows = if is_all { left_rows } else { distinct_rows(left_rows) };
+            left_rows.into_iter().filter(|row| {
+                let in_right = match right_counts.get_mut(row) {
+                    Some(count) if *count > 0 => { if is_all { *count -= 1; } true }
+                    _ => { false }
+                };
+
+                in_right == is_intersect
+            }).collect()
+        }
+    }
 }
 
-fn calculate_hash<T: Hash>(t: &T) -> u64 {
-    let mut s = DefaultHasher::new();
-    t.hash(&mut s);
-    s.finish()
+fn distinct_rows(rows: impl IntoIterator<Item = Vec<CoreValue>>) -> Vec<Vec<CoreValue>> {
+    let mut seen = HashSet::new();
+    rows.into_iter().filter(|row| seen.insert(row.clone())).collect()
 }
 
-fn match_select_item(row: Vec<FileColumn> /* The file's column that is checked if it is contained in the SELECT list */, projection: &SelectItem /* One item following SELECT (sa. files.name) */) -> Option<Vec<(String, String, String)>> {
-    match projection {
-        SelectItem::UnnamedExpr(expr) => { 
-            match expr {
-                Expr::Identifier(ident) => { 
-                    Some(vec![(calculate_hash(&ident).to_string(), "".to_owned(), ident.value.clone())])
-                }
-                Expr::Wildcard => { 
-                    Some(row.into_iter().map(|row| 
-                        (calculate_hash(&row).to_string(), "".to_owned(), row.to_string())
-                    ).collect::<Vec<(String, String, String)>>())
-                }
-                Expr::QualifiedWildcard(idents) => { todo!() }
-                Expr::CompoundIdentifier(idents) => { todo!() }
-                Expr::Case { operand, conditions, results, else_result } => { 
-                    //println!("column: {:#?}", column);
-                    //println!("projection: {:#?}", projection);
-                    //println!("operand: {:#?}", operand);
-                    //println!("conditions: {:#?}", conditions);
-                    //println!("results: {:#?}", results);
-                    //println!("else: {:#?}", else_result);
-
-                    //let expr = unbox(operand.unwrap());
-                    //let mut hash_map: HashMap<String, Vec<CoreFile>> = [(table_name.clone(), files)].iter().cloned().collect();
-                    //let result = consume_expr(expr.clone(), Some(&mut hash_map))?;
-
-                    Some(vec![(calculate_hash(&projection).to_string(), "".to_owned(), "TODO".to_owned())])
-                }
-                _ => unimplemented!() 
-            }    
+/**
+ * Checks each column of a set operation holds the same type of value in both queries, and converts them to the same type to be compared
+ * 
+ * Integers are converted to floats when the other query has floats in the same column, paths are converted to names when the other query has names
+**/
+fn coerce_set_operation_rows(left_rows: &mut [Vec<CoreValue>], right_rows: &mut [Vec<CoreValue>], op: &SetOperator) -> Result<(), CoreError> {
+    let column_count = left_rows.first().or(right_rows.first()).map(|row| row.len()).unwrap_or(0);
+    for column in 0..column_count {
+        let mut types: Vec<&'static str> = left_rows.iter().chain(right_rows.iter()).map(|row| value_type(&row[column])).filter(|value_type| !value_type.is_empty()).collect();
+        types.sort_unstable();
+        types.dedup();
+
+        let coerce: fn(CoreValue) -> CoreValue = match types.as_slice() {
+            [] | [_] => { continue }
+            ["float", "integer"] => { |value| match value { CoreValue::Integer(integer) => { CoreValue::Float(integer as f64) } value => { value } } }
+            ["path", "string"] => { |value| match value { CoreValue::Path(path) => { os_str_value(path.as_os_str()) } value => { value } } }
+            types => { return Err(CoreError::GeneralError(format!("The data types {} are incompatible in column {} of the {} operator", types.join(" and "), column + 1, op))) }
+        };
+
+        for row in left_rows.iter_mut().chain(right_rows.iter_mut()) {
+            row[column] = coerce(std::mem::replace(&mut row[column], CoreValue::Null));
         }
-        SelectItem::ExprWithAlias { expr, alias } => { todo!() }
-        SelectItem::QualifiedWildcard(_) => { todo!() }
-        SelectItem::Wildcard => { 
-            Some(row.into_iter().map(|row| 
-                (calculate_hash(&row).to_string(), "".to_owned(), row.to_string())
-            ).collect::<Vec<(String, String, String)>>())
-         }
+    }
+
+    Ok(())
+}
+
+/**
+ * The name of a value's type, names that are not valid UTF-8 are strings and NULL has no type
+**/
+fn value_type(value: &CoreValue) -> &'static str {
+    match value {
+        CoreValue::Null => { "" }
+        CoreValue::Integer(_) => { "integer" }
+        CoreValue::Float(_) => { "float" }
+        CoreValue::String(_) | CoreValue::Bytes(_) => { "string" }
+        CoreValue::Boolean(_) => { "boolean" }
+        CoreValue::Timestamp(_) => { "timestamp" }
+        CoreValue::Duration(_) => { "duration" }
+        CoreValue::Path(_) => { "path" }
     }
 }
 
 /**
- * Consumes and executes a SQL expression
+ * Finds the column of a set operation an ORDER BY item refers to, by its name or its position
 **/
-fn consume_expr(expr: Expr, tables: Option<&mut HashMap<String, Vec<CoreFile>>>) -> Result<ExprResult, CoreError> {
-    match expr {
-        Expr::Identifier(ident) => { consume_expr_ident(ident) }
-        Expr::CompoundIdentifier(mut idents) => { 
-            let column = idents.pop().unwrap();
-            let column_select = match consume_expr_ident(column) {
-                Ok(ExprResult::Select(select)) => { Ok(select) }
-                _ => { Err("No match arm provided in column_select") }
-            }?;
-
-            let table = idents.pop().unwrap();
-
-            Ok(ExprResult::CompoundSelect(table.value, column_select))
-        }
-        Expr::BinaryOp { left, op, right } => { 
-            let left_result = consume_expr(*left, None)?;
-            let right_result = consume_expr(*right, None)?;
-
-            if let Some(tables) = tables {
-                match (left_result, right_result) {
-                    (ExprResult::Select(left), ExprResult::Select(right)) => {
-                        Ok(ExprResult::BinaryOp(("".to_owned(), left), op, ("".to_owned(), right)))
-                    }
-                    (ExprResult::CompoundSelect(left_table_name, left), ExprResult::CompoundSelect(right_table_name, right)) => {
-                        Ok(ExprResult::BinaryOp((left_table_name, left), op, (right_table_name, right)))
-                    }
-                    (ExprResult::Value(left), ExprResult::Value(right)) => {
-                        Ok(ExprResult::BinaryOp(
-                            ("".to_owned(), Box::new(move |f| FileColumn::Name(Some(OsString::from(left.to_string()))))), 
-                            op, 
-                            ("".to_owned(), Box::new(move |f| FileColumn::Name(Some(OsString::from(right.to_string())))))
-                        ))
-                    }
-                    _ => { unimplemented!() }
-                }
-            } else {
-                panic!()
+fn result_set_column_index(result_set: &ResultSet, expr: &Expr) -> Result<usize, CoreError> {
+    let index = match expr {
+        Expr::Value(Value::Number(position, _)) => { position.parse::<usize>().ok().filter(|position| *position >= 1).map(|position| position - 1) }
+        Expr::Identifier(ident) => { result_set.columns.iter().position(|column| column.eq_ignore_ascii_case(&ident.value)) }
+        _ => { None }
+    };
+
+    match index {
+        Some(index) if index < result_set.columns.len() => { Ok(index) }
+        _ => { Err(CoreError::GeneralError(format!("The ORDER BY item {} must appear in the select list when the query contains a UNION, INTERSECT or EXCEPT operator", expr))) }
+    }
+}
+
+/**
+ * Consumes and executes a SQL select statement
+**/
+fn consume_select(mut select: Select, order_by: &[OrderByExpr], row_limit: RowLimit, context: &QueryContext) -> Result<ResultSet, CoreError> {
+    let from = std::mem::take(&mut select.from);
+    let mut selection = select.selection.take();
+    let mut tables = Vec::new();
+    for table_with_joins in &from {
+        tables = table_with_joins_schemas(table_with_joins, tables, context)?;
+    }
+    for (i, table) in tables.iter().enumerate() {
+        if tables[..i].iter().any(|other| other.name.eq_ignore_ascii_case(&table.name)) {
+            return Err(CoreError::GeneralError(format!("The table name '{}' is used more than once in the FROM clause, use aliases to distinguish them", table.name)));
+        }
+    }
+
+    let row_limit = match select.top.take() {
+        Some(top) if row_limit.is_unlimited() => { RowLimit::from_top(&top, context)? }
+        Some(_) => { return Err(CoreError::GeneralError("TOP cannot be combined with LIMIT, OFFSET or FETCH".to_owned())) }
+        None => { row_limit }
+    };
+
+    if row_limit.with_ties && order_by.is_empty() {
+        return Err(CoreError::GeneralError("WITH TIES requires an ORDER BY clause".to_owned()));
+    }
+
+    let mut rows: Vec<Row> = Vec::new();
+    // A single table is filtered as it is read, so that a row limit can stop reading the directory early
+    if from.len() == 1 && from[0].joins.is_empty() {
+        let scan_limit = if !is_aggregate_select(&select, order_by) && select.distinct.is_none() && order_by.is_empty() { row_limit.scan_limit() } else { None };
+        rows = scan_relation(&from[0].relation, &tables[0], selection.take().as_ref(), scan_limit, context)?;
+    } else {
+        // Tables separated by commas are cross joined
+        rows.push(context.outer_row());
+        let mut tables = tables.iter();
+        for table_with_join in from {
+            let schema = tables.next().expect("Each relation has a schema");
+            let mut joins = Vec::new();
+            for join in &table_with_join.joins {
+                let join_schema = tables.next().expect("Each relation has a schema");
+                let rows = match is_lateral(&join.relation) {
+                    true => { None }
+                    false => { Some(relation_rows(&join.relation, join_schema, &context.outer_row(), context)?.collect::<Vec<Row>>()) }
+                };
+                joins.push(JoinTable { relation: &join.relation, schema: join_schema, rows, operator: join.join_operator.clone() });
+            }
+
+            // A directory read from a column of a preceding table is read again for each row
+            let shared_rows = match is_lateral(&table_with_join.relation) {
+                true => { None }
+                false => { Some(join_relation_rows(&table_with_join.relation, schema, &context.outer_row(), &joins, context)?) }
+            };
+
+            let mut joined_rows = Vec::new();
+            for row in rows {
+                let relation_rows = match &shared_rows {
+                    Some(shared_rows) => { shared_rows.clone() }
+                    None => { join_relation_rows(&table_with_join.relation, schema, &row, &joins, context)? }
+                };
+
+                joined_rows.extend(relation_rows.into_iter().map(|relation_row| row.clone().join(relation_row)));
+            }
+
+            rows = joined_rows;
+        }
+    }
+
+    if let Some(selection) = selection {
+        let mut selected_rows = Vec::new();
+        for row in rows {
+            if evaluate_predicate(&selection, Scope::Row(&row), context)? {
+                selected_rows.push(row);
             }
         }
-        Expr::Value(value) => { Ok(ExprResult::Value(value)) }
-        _ => { unimplemented!( )}
+
+        rows = selected_rows;
     }
+
+    project_rows(rows, &select, &tables, order_by, &row_limit, context)
 }
 
-fn consume_expr_ident(ident: Ident) -> Result<ExprResult, CoreError> {    
-    let file_column = FileColumn::from_str(ident.value.as_str())?;
-    Ok(ExprResult::Select(Box::new(move |c| c.column(&file_column))))
+/**
+ * A table joined to the table before it, the rows of a table read from a column of another table are read for each row they are joined to
+**/
+struct JoinTable<'a> {
+    relation: &'a TableFactor,
+    schema: &'a TableSchema,
+    rows: Option<Vec<Row>>,
+    operator: JoinOperator,
 }
 
 /**
- * Consumes and executes a SQL operation
+ * Reads the rows of a relation and joins them with the tables joined to it in order, each join keeping the tables of the joins before it
 **/
-fn consume_op(left: Value, op: &BinaryOperator, right: Value) -> Result<Value, CoreError> {
-    //println!("op: {{ left: {:?}, right: {:?} }}", left, right);
-    match op {
-        BinaryOperator::Plus => { 
-            match (left, right) {
-                (Value::Number(a), Value::Number(b)) => { Ok(Value::Number((a.parse::<i32>().unwrap() + b.parse::<i32>().unwrap()).to_string())) }
-                (Value::Number(a), Value::Null) => { Ok(Value::Number(a)) }
-                (Value::SingleQuotedString(mut a), Value::SingleQuotedString(b)) => { a.push_str(b.as_str()); Ok(Value::SingleQuotedString(a)) }
-                (Value::SingleQuotedString(a), Value::Null) => { Ok(Value::SingleQuotedString(a)) }
-                (Value::Boolean(a), Value::Null) => { Ok(Value::Boolean(a)) }
-                (Value::Null, Value::Number(b)) => { Ok(Value::Number(b)) }
-                (Value::Null, Value::SingleQuotedString(b)) => { Ok(Value::SingleQuotedString(b)) }
-                (Value::Null, Value::Boolean(b)) => { Ok(Value::Boolean(b)) }
-                (Value::Null, Value::Null) => { Ok(Value::Null) }
-                _ => { Err(CoreError::GeneralError("The data types are invalid for the specified operator".to_owned())) }
-            }
-        }
-        BinaryOperator::Minus => {
-            match (left, right) {
-                (Value::Number(a), Value::Number(b)) => { Ok(Value::Number((a.parse::<i32>().unwrap() - b.parse::<i32>().unwrap()).to_string())) }
-                (Value::Number(a), Value::Null) => { Ok(Value::Number(a)) }
-                (Value::SingleQuotedString(a), Value::Null) => { Ok(Value::SingleQuotedString(a)) }
-                (Value::Boolean(a), Value::Null) => { Ok(Value::Boolean(a)) }
-                (Value::Null, Value::Number(b)) => { Ok(Value::Number(b)) }
-                (Value::Null, Value::SingleQuotedString(b)) => { Ok(Value::SingleQuotedString(b)) }
-                (Value::Null, Value::Boolean(b)) => { Ok(Value::Boolean(b)) }
-                (Value::Null, Value::Null) => { Ok(Value::Null) }
-                _ => { Err(CoreError::GeneralError("The data types are invalid for the specified operator".to_owned())) }
+fn join_relation_rows(relation: &TableFactor, schema: &TableSchema, row: &Row, joins: &[JoinTable], context: &QueryContext) -> Result<Vec<Row>, CoreError> {
+    let mut rows = relation_rows(relation, schema, row, context)?.collect::<Vec<Row>>();
+    let mut left_tables = vec![schema];
+    for join in joins {
+        rows = match &join.rows {
+            Some(join_table_rows) => { join_rows(rows, &left_tables, join.schema, join_table_rows.clone(), join.operator.clone(), context)? }
+            None => {
+                if !matches!(join.operator, JoinOperator::Inner(_) | JoinOperator::LeftOuter(_) | JoinOperator::CrossJoin) {
+                    return Err(CoreError::GeneralError(format!("The directory read from the column {} can only be INNER, LEFT or CROSS joined", join.relation)));
+                }
+
+                let mut joined_rows = Vec::new();
+                for row in rows {
+                    let join_table_rows = relation_rows(join.relation, join.schema, &row, context)?.collect::<Vec<Row>>();
+                    joined_rows.extend(join_rows(vec![row], &left_tables, join.schema, join_table_rows, join.operator.clone(), context)?);
+                }
+                joined_rows
             }
+        };
+        left_tables.push(join.schema);
+    }
+
+    Ok(rows)
+}
+
+/**
+ * The key a row is joined on, a key holding a NULL never matches so it is given a key unique to its row
+**/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum JoinKey {
+    Values(Vec<CoreValue>),
+    Null(/*is the joined table*/ bool, usize),
+}
+
+/**
+ * The condition rows are joined on, the equalities between the joined table and the tables before it are matched by hashing their values
+ * and the rest of the condition is evaluated for each pair of rows with matching keys
+**/
+#[derive(Debug, Default)]
+struct JoinCondition {
+    keys: Vec<Expr>,
+    join_keys: Vec<Expr>,
+    residual: Option<Expr>,
+}
+
+/**
+ * Joins the rows read so far with the files of the joined table
+ * 
+ * The ON condition can be any predicate, USING and NATURAL joins match on columns of the same name.
+ * The tables of a side without a match have every column NULL
+**/
+fn join_rows(rows: Vec<Row>, left_tables: &[&TableSchema], join_table: &TableSchema, join_table_rows: Vec<Row>, join_operator: JoinOperator, context: &QueryContext) -> Result<Vec<Row>, CoreError> {
+    let constraint = match &join_operator {
+        JoinOperator::Inner(JoinConstraint::None) | JoinOperator::CrossJoin => {
+            return Ok(enumerable::cross_join(rows, join_table_rows, Box::new(|row: Row, join_row| row.join(join_row))));
+        }
+        JoinOperator::Inner(constraint) | JoinOperator::LeftOuter(constraint) | JoinOperator::RightOuter(constraint) | JoinOperator::FullOuter(constraint) => { constraint }
+        _ => { return Err(CoreError::GeneralError(format!("The join {:?} is not supported", join_operator))) }
+    };
+
+    let condition = join_condition(constraint, left_tables, join_table)?;
+    let join_columns: Option<Rc<[String]>> = match constraint {
+        JoinConstraint::Using(_) | JoinConstraint::Natural => { Some(condition.join_keys.iter().filter_map(|key| match key { Expr::CompoundIdentifier(idents) => { idents.last().map(|ident| ident.value.clone()) } _ => { None } }).collect()) }
+        _ => { None }
+    };
+
+    let key_values = |exprs: &[Expr], rows: &[Row]| rows.iter()
+        .map(|row| exprs.iter().map(|expr| evaluate_expr(expr, Scope::Row(row), context)).collect::<Result<Vec<CoreValue>, CoreError>>())
+        .collect::<Result<Vec<Vec<CoreValue>>, CoreError>>();
+    let (row_values, join_row_values) = (key_values(&condition.keys, &rows)?, key_values(&condition.join_keys, &join_table_rows)?);
+
+    // An equality is only hashed when every value on both sides is of the same kind, others such as a number and a string holding one are coerced by the residual
+    let is_hashed = (0..condition.keys.len()).map(|k| {
+        let mut kinds = row_values.iter().chain(&join_row_values).map(|values| &values[k]).filter(|value| !value.is_null()).map(CoreValue::key_kind);
+        let first = kinds.next();
+        kinds.all(|kind| Some(kind) == first)
+    }).collect::<Vec<bool>>();
+    let unhashed = (0..condition.keys.len()).filter(|k| !is_hashed[*k]).map(|k| Expr::BinaryOp { left: Box::new(condition.keys[k].clone()), op: BinaryOperator::Eq, right: Box::new(condition.join_keys[k].clone()) });
+    let residual = unhashed.chain(condition.residual.clone()).reduce(|left, right| Expr::BinaryOp { left: Box::new(left), op: BinaryOperator::And, right: Box::new(right) });
+
+    let keyed_rows = rows.into_iter().zip(row_values).enumerate().map(|(i, (row, values))| (join_key(values, &is_hashed, false, i), row)).collect::<Vec<(JoinKey, Row)>>();
+    let keyed_join_rows = join_table_rows.into_iter().zip(join_row_values).enumerate().map(|(i, (row, values))| (join_key(values, &is_hashed, true, i), row)).collect::<Vec<(JoinKey, Row)>>();
+
+    let null_row = join_table.null_row();
+    let left_null_row = left_tables.iter().fold(Row::default(), |row, table| row.join(table.null_row()));
+    let joined_row = move |row: Row, join_row: Row| match &join_columns {
+        Some(join_columns) => { row.join(join_row).with_join_columns(join_columns) }
+        None => { row.join(join_row) }
+    };
+
+    if let Some(residual) = &residual {
+        return join_rows_with_residual(keyed_rows, keyed_join_rows, |row| evaluate_predicate(residual, Scope::Row(row), context), &join_operator, joined_row, left_null_row, null_row);
+    }
+
+    let key = |(key, _): &(JoinKey, Row)| key.clone();
+    Ok(match join_operator {
+        JoinOperator::LeftOuter(_) => {
+            enumerable::left_join(keyed_rows, keyed_join_rows, Box::new(key), Box::new(key), Box::new(move |(_, row), join_row| {
+                joined_row(row, join_row.map(|(_, join_row)| join_row).unwrap_or_else(|| null_row.clone()))
+            }))
+        }
+        JoinOperator::RightOuter(_) => {
+            enumerable::right_join(keyed_rows, keyed_join_rows, Box::new(key), Box::new(key), Box::new(move |row, (_, join_row)| {
+                joined_row(row.map(|(_, row)| row).unwrap_or_else(|| left_null_row.clone()), join_row)
+            }))
+        }
+        JoinOperator::FullOuter(_) => {
+            enumerable::full_join(keyed_rows, keyed_join_rows, Box::new(key), Box::new(key), Box::new(move |row, join_row| {
+                joined_row(row.map(|(_, row)| row).unwrap_or_else(|| left_null_row.clone()), join_row.map(|(_, join_row)| join_row).unwrap_or_else(|| null_row.clone()))
+            }))
+        }
+        _ => {
+            enumerable::inner_join(keyed_rows, keyed_join_rows, Box::new(key), Box::new(key), Box::new(move |(_, row), (_, join_row)| joined_row(row, join_row)))
+        }
+    })
+}
+
+/**
+ * Joins the rows whose keys match by hashing, then keeps the pairs of rows the rest of the condition is true for
+ *
+ * Without any equalities every pair of rows has the same key, so each pair is checked in a nested loop.
+ * A row is only padded with NULLs when none of its pairs are kept
+**/
+fn join_rows_with_residual(keyed_rows: Vec<(JoinKey, Row)>, keyed_join_rows: Vec<(JoinKey, Row)>, residual: impl Fn(&Row) -> Result<bool, CoreError>, join_operator: &JoinOperator, joined_row: impl Fn(Row, Row) -> Row, left_null_row: Row, null_row: Row) -> Result<Vec<Row>, CoreError> {
+    let keep_rows = matches!(join_operator, JoinOperator::LeftOuter(_) | JoinOperator::FullOuter(_));
+    let keep_join_rows = matches!(join_operator, JoinOperator::RightOuter(_) | JoinOperator::FullOuter(_));
+
+    let indexed_rows = keyed_rows.iter().enumerate().map(|(i, (key, _))| (key.clone(), i)).collect::<Vec<(JoinKey, usize)>>();
+    let indexed_join_rows = keyed_join_rows.iter().enumerate().map(|(i, (key, _))| (key.clone(), i)).collect::<Vec<(JoinKey, usize)>>();
+    let mut pairs = enumerable::inner_join(indexed_rows, indexed_join_rows, Box::new(|(key, _)| key.clone()), Box::new(|(key, _)| key.clone()), Box::new(|(_, i), (_, j)| (i, j)));
+    pairs.sort_by_key(|(i, _)| *i);
+
+    let mut matched = vec![false; keyed_rows.len()];
+    let mut matched_join_rows = vec![false; keyed_join_rows.len()];
+    let mut joined_rows = Vec::new();
+    for (i, j) in pairs {
+        let row = joined_row(keyed_rows[i].1.clone(), keyed_join_rows[j].1.clone());
+        if residual(&row)? {
+            matched[i] = true;
+            matched_join_rows[j] = true;
+            joined_rows.push((i, row));
         }
-        BinaryOperator::Eq => { Ok(Value::Boolean(left == right)) }
-        _ => { unimplemented!() }
     }
+
+    if keep_rows {
+        joined_rows.extend(keyed_rows.iter().enumerate().filter(|(i, _)| !matched[*i]).map(|(i, (_, row))| (i, joined_row(row.clone(), null_row.clone()))));
+        joined_rows.sort_by_key(|(i, _)| *i);
+    }
+
+    let mut rows = joined_rows.into_iter().map(|(_, row)| row).collect::<Vec<Row>>();
+    if keep_join_rows {
+        rows.extend(keyed_join_rows.into_iter().enumerate().filter(|(j, _)| !matched_join_rows[*j]).map(|(_, (_, join_row))| joined_row(left_null_row.clone(), join_row)));
+    }
+
+    Ok(rows)
 }
 
-fn consume_relation(relation: TableFactor) -> Result<(String, Vec<CoreFile>), CoreError> {
-    match relation {
-        TableFactor::Table { name, alias, args, with_hints } => {
-            if let Some(table_name) = name.0.first() {
-                let table_alias = if alias.is_some() { alias.unwrap().name.value } else { table_name.value.clone() };
-                return Ok((table_alias, consume_table_name(table_name.value.as_str())?));
+/**
+ * Splits the join condition into the expressions the rows before the join and the rows of the joined table are matched on, and the rest of the condition
+ *
+ * A USING column is taken from the first table before the join that has it, a NATURAL join matches on every column both sides have
+**/
+fn join_condition(constraint: &JoinConstraint, left_tables: &[&TableSchema], join_table: &TableSchema) -> Result<JoinCondition, CoreError> {
+    let column_names = match constraint {
+        JoinConstraint::On(expr) => {
+            let mut condition = JoinCondition::default();
+            let mut residuals = Vec::new();
+            for conjunct in conjuncts(expr) {
+                match conjunct {
+                    Expr::BinaryOp { left, op: BinaryOperator::Eq, right } if references_table(right, &join_table.name) && !references_table(left, &join_table.name) => {
+                        condition.keys.push(*left.clone());
+                        condition.join_keys.push(*right.clone());
+                    }
+                    Expr::BinaryOp { left, op: BinaryOperator::Eq, right } if references_table(left, &join_table.name) && !references_table(right, &join_table.name) => {
+                        condition.keys.push(*right.clone());
+                        condition.join_keys.push(*left.clone());
+                    }
+                    conjunct => { residuals.push(conjunct.clone()) }
+                }
+            }
+
+            condition.residual = residuals.into_iter().reduce(|left, right| Expr::BinaryOp { left: Box::new(left), op: BinaryOperator::And, right: Box::new(right) });
+            return Ok(condition);
+        }
+        JoinConstraint::Using(idents) => { idents.iter().map(|ident| ident.value.clone()).collect() }
+        JoinConstraint::Natural => {
+            join_table.column_names().into_iter().filter(|column_name| left_tables.iter().any(|table| table.has_column(column_name))).collect::<Vec<String>>()
+        }
+        JoinConstraint::None => { Vec::new() }
+    };
+
+    let column = |table: &TableSchema, column_name: &str| Expr::CompoundIdentifier(vec![Ident::new(table.name.clone()), Ident::new(column_name)]);
+    let mut condition = JoinCondition::default();
+    for column_name in column_names {
+        match (left_tables.iter().find(|table| table.has_column(&column_name)), join_table.has_column(&column_name)) {
+            (Some(table), true) => {
+                condition.keys.push(column(table, &column_name));
+                condition.join_keys.push(column(join_table, &column_name));
             }
-            panic!()
+            _ => { return Err(CoreError::GeneralError(format!("The column '{}' in the USING clause is not in both sides of the join", column_name))) }
         }
-        _ => { unimplemented!() }
     }
+
+    Ok(condition)
 }
 
-fn consume_table_name(table_name: &str) -> Result<Vec<CoreFile>, CoreError> {
-    let mut files: Vec<CoreFile> = Vec::new();
-    let paths = fs::read_dir(table_name)?;
-    for path in paths {
-        if let Ok(path) = path {
-            files.push(CoreFile::from(path));
-        }                        
+/**
+ * The predicates joined by AND in an expression
+**/
+fn conjuncts(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::BinaryOp { left, op: BinaryOperator::And, right } => { conjuncts(left).into_iter().chain(conjuncts(right)).collect() }
+        Expr::Nested(expr) => { conjuncts(expr) }
+        expr => { vec![expr] }
     }
+}
 
-    Ok(files)
+/**
+ * The key of a row from the values of its side of the equalities, only the hashed equalities are part of the key
+**/
+fn join_key(values: Vec<CoreValue>, is_hashed: &[bool], is_join_table: bool, i: usize) -> JoinKey {
+    match values.iter().any(CoreValue::is_null) {
+        true => { JoinKey::Null(is_join_table, i) }
+        false => { JoinKey::Values(values.into_iter().zip(is_hashed).filter(|(_, is_hashed)| **is_hashed).map(|(value, _)| value.key_value()).collect()) }
+    }
 }
 
+/**
+ * Whether the expression references a column qualified by the table name
+**/
+fn references_table(expr: &Expr, table_name: &str) -> bool {
+    match expr {
+        Expr::CompoundIdentifier(idents) => { idents.len() > 1 && idents[idents.len() - 2].value.eq_ignore_ascii_case(table_name) }
+        Expr::BinaryOp { left, right, .. } => { references_table(left, table_name) || references_table(right, table_name) }
+        Expr::UnaryOp { expr, .. } | Expr::Nested(expr) => { references_table(expr, table_name) }
+        Expr::Function(function) => { function_arg_exprs(function).any(|arg| references_table(arg, table_name)) }
+        _ => { false }
+    }
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/**
+ * Appends the name and columns of each table in a FROM item to the tables before it, a table is named by its alias when it has one
+**/
+fn table_with_joins_schemas(table_with_joins: &TableWithJoins, mut tables: Vec<TableSchema>, context: &QueryContext) -> Result<Vec<TableSchema>, CoreError> {
+    for relation in std::iter::once(&table_with_joins.relation).chain(table_with_joins.joins.iter().map(|join| &join.relation)) {
+        let schema = relation_schema(relation, &tables, context)?;
+        tables.push(schema);
+    }
 
-    const PATH_TO_TEST_DIR: &str = "./test/";
+    Ok(tables)
+}
 
-    #[test]
-    fn consume_op_add_number_number() {
-        let left_val = Value::Number("2".to_owned());
-        let right_val = Value::Number("2".to_owned());
-        let result = consume_op(left_val, &BinaryOperator::Plus, right_val);
+/**
+ * A table named after a common table expression in scope has the columns of the expression, any other table is a directory
+ *
+ * A directory read from a column of one of the tables before it has the same root as that table, any other directory is its own root
+**/
+fn relation_schema(relation: &TableFactor, tables: &[TableSchema], context: &QueryContext) -> Result<TableSchema, CoreError> {
+    let name = match relation {
+        TableFactor::Table { name, alias: None, .. } if is_lateral(relation) => {
+            return Err(CoreError::GeneralError(format!("The directory read from the column {} must be given an alias", name)))
+        }
+        TableFactor::Table { name, .. } => { name }
+        _ => { return Ok(TableSchema::files(&relation_name(relation))) }
+    };
+
+    Ok(match relation_cte_with_root(relation, context) {
+        Some((result_set, root)) => { TableSchema { name: relation_name(relation), columns: Some(Rc::from(result_set.columns.clone())), formats: Rc::from(result_set.formats.clone()), root } }
+        None if is_lateral(relation) => {
+            let root = tables.iter().find(|table| table.name.eq_ignore_ascii_case(&name.0[0].value)).and_then(|table| table.root.clone());
+            TableSchema { root, ..TableSchema::files(&relation_name(relation)) }
+        }
+        None => { TableSchema { root: name.0.first().map(|directory| PathBuf::from(&directory.value)), ..TableSchema::files(&relation_name(relation)) } }
+    })
+}
 
-        assert_eq!(match result {
-            Ok(Value::Number(string)) => { string }
-            _ => { panic!("Incorrect enum variant expected") }
-        }, "4".to_owned());
+fn relation_name(relation: &TableFactor) -> String {
+    match relation {
+        TableFactor::Table { alias: Some(alias), .. } => { alias.name.value.clone() }
+        TableFactor::Table { name, .. } => { name.0.first().map(|ident| ident.value.clone()).unwrap_or_default() }
+        _ => { relation.to_string() }
     }
+}
 
-    #[test]
-    fn consume_op_add_string_string() {
-        let left_val = Value::SingleQuotedString("2".to_owned());
-        let right_val = Value::SingleQuotedString("2".to_owned());
-        let result = consume_op(left_val, &BinaryOperator::Plus, right_val);
+/**
+ * Whether two expressions are the same, a column is the same whether or not it is qualified by the only table in the FROM clause that has it
+**/
+fn is_same_column(left: &Expr, right: &Expr, tables: &[TableSchema]) -> bool {
+    let qualified_idents = |expr: &Expr| match expr {
+        Expr::Identifier(ident) => {
+            let mut matching = tables.iter().filter(|table| table.has_column(&ident.value));
+            match (matching.next(), matching.next()) {
+                (Some(table), None) => { Some(vec![table.name.clone(), ident.value.clone()]) }
+                _ => { Some(vec![ident.value.clone()]) }
+            }
+        }
+        Expr::CompoundIdentifier(idents) => { Some(idents.iter().map(|ident| ident.value.clone()).collect()) }
+        _ => { None }
+    };
 
-        assert_eq!(match result {
-            Ok(Value::SingleQuotedString(string)) => { string }
-            _ => { panic!("Incorrect enum variant expected") }
-        }, "22".to_owned());
+    match (qualified_idents(left), qualified_idents(right)) {
+        (Some(left), Some(right)) => { left.len() == right.len() && left.iter().zip(&right).all(|(left, right)| left.eq_ignore_ascii_case(right)) }
+        _ => { left == right }
     }
+}
 
-    #[test]
-    fn consume_op_subtract_string_string() {
-        let left_val = Value::SingleQuotedString("2".to_owned());
-        let right_val = Value::SingleQuotedString("2".to_owned());
-        let result = consume_op(left_val, &BinaryOperator::Minus, right_val);
+/**
+ * Evaluates the select list for each row, or for each group of rows when the select is aggregated
+ * 
+ * DISTINCT, ORDER BY and the row limit are then applied to the selected values
+**/
+fn project_rows(rows: Vec<Row>, select: &Select, tables: &[TableSchema], order_by: &[OrderByExpr], row_limit: &RowLimit, context: &QueryContext) -> Result<ResultSet, CoreError> {
+    let (columns, select_exprs): (Vec<String>, Vec<Expr>) = projection_exprs(&select.projection, tables)?.into_iter().unzip();
+    let formats = select_exprs.iter().map(|expr| column_format(expr, tables)).collect();
+    let order_exprs = order_by.iter().map(|order|
+        resolve_select_list_expr(&order.expr, &select.projection, tables)
+    ).collect::<Result<Vec<Expr>, CoreError>>()?;
+
+    let mut keyed_rows = Vec::new();
+    if is_aggregate_select(select, order_by) {
+        // Columns named in GROUP BY take priority over select list aliases of the same name
+        let group_by = group_by_exprs(select)?.iter().map(|expr| match expr {
+            Expr::Identifier(ident) if FileColumn::from_str(&ident.value).is_ok() => { Ok(expr.clone()) }
+            _ => { resolve_select_list_expr(expr, &select.projection, tables) }
+        }).collect::<Result<Vec<Expr>, CoreError>>()?;
+
+        for group in group_rows(rows, &group_by, context)? {
+            let scope = Scope::Group(&group, &group_by, tables);
+            if let Some(having) = &select.having {
+                if !evaluate_predicate(having, scope, context)? {
+                    continue;
+                }
+            }
 
-        assert!(match result {
-            Err(CoreError::GeneralError(_)) => { true }
-            _ => { panic!("Incorrect enum variant expected") }
-        });
+            keyed_rows.push(evaluate_keyed_row(&select_exprs, &order_exprs, scope, context)?);
+        }
+    } else {
+        for row in &rows {
+            keyed_rows.push(evaluate_keyed_row(&select_exprs, &order_exprs, Scope::Row(row), context)?);
+        }
     }
 
-    #[test]
+    // NULLs are not distinct from each other
+    if let Some(distinct) = &select.distinct {
+        if let Distinct::On(_) = distinct {
+            return Err(CoreError::GeneralError("DISTINCT ON is not supported".to_owned()));
+        }
+
+        if let Some(expr) = order_exprs.iter().find(|expr| !select_exprs.iter().any(|select_expr| is_same_column(expr, select_expr, tables))) {
+            return Err(CoreError::GeneralError(format!("The ORDER BY item {} must appear in the select list when SELECT DISTINCT is used", expr)));
+        }
+
+        let mut seen = HashSet::new();
+        keyed_rows.retain(|(_, values)| seen.insert(values.clone()));
+    }
+
+    if !order_by.is_empty() {
+        keyed_rows = order_rows(keyed_rows, order_by, context.options.time_zone)?;
+    }
+
+    Ok(ResultSet { columns, formats, rows: limit_rows(keyed_rows, row_limit, order_by, context.options.time_zone).into_iter().map(|(_, row)| row).collect() })
+}
+
+/**
+ * The format of a selected column is that of the table column it is, an expression of a column is displayed as its values are
+**/
+fn column_format(expr: &Expr, tables: &[TableSchema]) -> ColumnFormat {
+    let (table, column_name) = match expr {
+        Expr::Identifier(ident) => {
+            let mut tables = tables.iter().filter(|table| table.has_column(&ident.value));
+            match (tables.next(), tables.next()) {
+                (Some(table), None) => { (table, &ident.value) }
+                _ => { return ColumnFormat::Plain }
+            }
+        }
+        Expr::CompoundIdentifier(idents) if idents.len() == 2 => {
+            match tables.iter().find(|table| table.name.eq_ignore_ascii_case(&idents[0].value)) {
+                Some(table) => { (table, &idents[1].value) }
+                None => { return ColumnFormat::Plain }
+            }
+        }
+        Expr::Nested(expr) => { return column_format(expr, tables) }
+        _ => { return ColumnFormat::Plain }
+    };
+
+    table.column_format(column_name)
+}
+
+fn evaluate_keyed_row(select_exprs: &[Expr], order_exprs: &[Expr], scope: Scope, context: &QueryContext) -> Result<KeyedRow, CoreError> {
+    let keys = order_exprs.iter().map(|expr| evaluate_expr(expr, scope, context)).collect::<Result<Vec<CoreValue>, CoreError>>()?;
+    let values = select_exprs.iter().map(|expr| evaluate_expr(expr, scope, context)).collect::<Result<Vec<CoreValue>, CoreError>>()?;
+
+    Ok((keys, values))
+}
+
+/**
+ * Splits the rows into groups with equal GROUP BY values, the groups are kept in the order they were first seen
+ * 
+ * Without a GROUP BY every row belongs to a single group, even when there are no rows
+**/
+fn group_rows(rows: Vec<Row>, group_by: &[Expr], context: &QueryContext) -> Result<Vec<Vec<Row>>, CoreError> {
+    if group_by.is_empty() {
+        return Ok(vec![rows]);
+    }
+
+    let mut groups: Vec<Vec<Row>> = Vec::new();
+    let mut lookup: HashMap<Vec<CoreValue>, usize> = HashMap::new();
+    for row in rows {
+        let key = group_by.iter().map(|expr| evaluate_expr(expr, Scope::Row(&row), context)).collect::<Result<Vec<CoreValue>, CoreError>>()?;
+        match lookup.get(&key) {
+            Some(index) => { groups[*index].push(row) }
+            None => {
+                lookup.insert(key, groups.len());
+                groups.push(vec![row]);
+            }
+        }
+    }
+
+    Ok(groups)
+}
+
+/**
+ * A select is aggregated when it has a GROUP BY or HAVING clause, or when its select list or ORDER BY uses an aggregate function
+**/
+fn is_aggregate_select(select: &Select, order_by: &[OrderByExpr]) -> bool {
+    !matches!(&select.group_by, GroupByExpr::Expressions(exprs, _) if exprs.is_empty()) || select.having.is_some() || select.projection.iter().any(|item| match item {
+        SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => { contains_aggregate(expr) }
+        _ => { false }
+    }) || order_by.iter().any(|order| contains_aggregate(&order.expr))
+}
+
+/**
+ * The expressions grouped by, GROUP BY ALL is not supported
+**/
+fn group_by_exprs(select: &Select) -> Result<&[Expr], CoreError> {
+    match &select.group_by {
+        GroupByExpr::Expressions(exprs, modifiers) if modifiers.is_empty() => { Ok(exprs) }
+        group_by => { Err(CoreError::GeneralError(format!("{} is not supported", group_by))) }
+    }
+}
+
+/**
+ * The expressions passed to a function, wildcards such as COUNT(*) are skipped
+**/
+fn function_arg_exprs(function: &Function) -> impl Iterator<Item = &Expr> {
+    let args = match &function.args {
+        FunctionArguments::List(list) => { &list.args[..] }
+        _ => { &[] }
+    };
+
+    args.iter().filter_map(|arg| match arg {
+        FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) | FunctionArg::Named { arg: FunctionArgExpr::Expr(expr), .. } | FunctionArg::ExprNamed { arg: FunctionArgExpr::Expr(expr), .. } => { Some(expr) }
+        _ => { None }
+    })
+}
+
+fn contains_aggregate(expr: &Expr) -> bool {
+    match expr {
+        Expr::Function(function) if Aggregate::from_str(&function.name.to_string()).is_ok() => { true }
+        expr => { child_exprs(expr).into_iter().any(contains_aggregate) }
+    }
+}
+
+/**
+ * The expressions directly within an expression
+ * 
+ * The body of a subquery is not included, the columns and aggregates in it belong to the subquery
+**/
+fn child_exprs(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Identifier(_) | Expr::CompoundIdentifier(_) | Expr::Value(_) | Expr::IntroducedString { .. } | Expr::TypedString { .. } | Expr::MatchAgainst { .. }
+            | Expr::Wildcard(_) | Expr::QualifiedWildcard(_, _) | Expr::Exists { .. } | Expr::Subquery(_) => { Vec::new() }
+        Expr::IsFalse(expr) | Expr::IsNotFalse(expr) | Expr::IsTrue(expr) | Expr::IsNotTrue(expr) | Expr::IsNull(expr) | Expr::IsNotNull(expr) | Expr::IsUnknown(expr) | Expr::IsNotUnknown(expr)
+            | Expr::UnaryOp { expr, .. } | Expr::Cast { expr, .. } | Expr::Extract { expr, .. } | Expr::Ceil { expr, .. } | Expr::Floor { expr, .. } | Expr::Collate { expr, .. }
+            | Expr::Nested(expr) | Expr::Named { expr, .. } | Expr::CompositeAccess { expr, .. } | Expr::InSubquery { expr, .. } | Expr::OuterJoin(expr) | Expr::Prior(expr) => { vec![expr] }
+        Expr::IsDistinctFrom(left, right) | Expr::IsNotDistinctFrom(left, right) | Expr::BinaryOp { left, right, .. } | Expr::AnyOp { left, right, .. } | Expr::AllOp { left, right, .. }
+            | Expr::Like { expr: left, pattern: right, .. } | Expr::ILike { expr: left, pattern: right, .. } | Expr::SimilarTo { expr: left, pattern: right, .. } | Expr::RLike { expr: left, pattern: right, .. }
+            | Expr::InUnnest { expr: left, array_expr: right, .. } | Expr::AtTimeZone { timestamp: left, time_zone: right } | Expr::Position { expr: left, r#in: right } => { vec![left, right] }
+        Expr::InList { expr, list, .. } => { std::iter::once(&**expr).chain(list).collect() }
+        Expr::Between { expr, low, high, .. } => { vec![expr, low, high] }
+        Expr::Convert { expr, styles, .. } => { std::iter::once(&**expr).chain(styles).collect() }
+        Expr::Substring { expr, substring_from, substring_for, .. } => { std::iter::once(expr).chain(substring_from).chain(substring_for).map(|expr| &**expr).collect() }
+        Expr::Trim { expr, trim_what, trim_characters, .. } => { std::iter::once(expr).chain(trim_what).map(|expr| &**expr).chain(trim_characters.iter().flatten()).collect() }
+        Expr::Overlay { expr, overlay_what, overlay_from, overlay_for } => { vec![&**expr, &**overlay_what, &**overlay_from].into_iter().chain(overlay_for.as_deref()).collect() }
+        Expr::JsonAccess { value, path } => {
+            std::iter::once(&**value).chain(path.path.iter().filter_map(|elem| match elem { JsonPathElem::Bracket { key } => { Some(key) } JsonPathElem::Dot { .. } => { None } })).collect()
+        }
+        Expr::MapAccess { column, keys } => { std::iter::once(&**column).chain(keys.iter().map(|key| &key.key)).collect() }
+        Expr::Function(function) => { function_arg_exprs(function).collect() }
+        Expr::Method(method) => { std::iter::once(&*method.expr).chain(method.method_chain.iter().flat_map(function_arg_exprs)).collect() }
+        Expr::Case { operand, conditions, results, else_result } => { operand.iter().chain(else_result).map(|expr| &**expr).chain(conditions).chain(results).collect() }
+        Expr::GroupingSets(sets) | Expr::Cube(sets) | Expr::Rollup(sets) => { sets.iter().flatten().collect() }
+        Expr::Tuple(exprs) | Expr::Struct { values: exprs, .. } | Expr::Array(Array { elem: exprs, .. }) => { exprs.iter().collect() }
+        Expr::Dictionary(fields) => { fields.iter().map(|field| &*field.value).collect() }
+        Expr::Map(map) => { map.entries.iter().flat_map(|entry| vec![&*entry.key, &*entry.value]).collect() }
+        Expr::Subscript { expr, subscript } => {
+            match &**subscript {
+                Subscript::Index { index } => { vec![expr, index] }
+                Subscript::Slice { lower_bound, upper_bound, stride } => { std::iter::once(&**expr).chain(lower_bound).chain(upper_bound).chain(stride).collect() }
+            }
+        }
+        Expr::Interval(interval) => { vec![&interval.value] }
+        Expr::Lambda(lambda) => { vec![&lambda.body] }
+    }
+}
+
+/**
+ * The rows kept by TOP, LIMIT, OFFSET and FETCH
+**/
+#[derive(Debug, Clone, Default, PartialEq)]
+struct RowLimit {
+    offset: usize,
+    quantity: Option<Quantity>,
+    with_ties: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Quantity {
+    Rows(usize),
+    Percent(f64),
+}
+
+impl RowLimit {
+    fn from_query(limit: Option<&Expr>, offset: Option<&Offset>, fetch: Option<&Fetch>, context: &QueryContext) -> Result<RowLimit, CoreError> {
+        let offset = match offset {
+            Some(offset) => { evaluate_row_count(&offset.value, context)? }
+            None => { 0 }
+        };
+
+        let (quantity, with_ties) = match (limit, fetch) {
+            (Some(_), Some(_)) => { return Err(CoreError::GeneralError("LIMIT cannot be combined with FETCH".to_owned())) }
+            (Some(limit), None) => { (Some(Quantity::Rows(evaluate_row_count(limit, context)?)), false) }
+            (None, Some(fetch)) => {
+                // FETCH FIRST ROWS ONLY without a quantity fetches a single row
+                let quantity = match &fetch.quantity {
+                    Some(quantity) if fetch.percent => { Quantity::Percent(evaluate_percent(quantity, context)?) }
+                    Some(quantity) => { Quantity::Rows(evaluate_row_count(quantity, context)?) }
+                    None => { Quantity::Rows(1) }
+                };
+
+                (Some(quantity), fetch.with_ties)
+            }
+            (None, None) => { (None, false) }
+        };
+
+        Ok(RowLimit { offset, quantity, with_ties })
+    }
+
+    fn from_top(top: &Top, context: &QueryContext) -> Result<RowLimit, CoreError> {
+        let quantity = top.quantity.as_ref().map(|quantity| match quantity {
+            TopQuantity::Expr(expr) => { expr.clone() }
+            TopQuantity::Constant(constant) => { Expr::Value(Value::Number(constant.to_string(), false)) }
+        });
+
+        let quantity = match &quantity {
+            Some(quantity) if top.percent => { Some(Quantity::Percent(evaluate_percent(quantity, context)?)) }
+            Some(quantity) => { Some(Quantity::Rows(evaluate_row_count(quantity, context)?)) }
+            None => { None }
+        };
+
+        Ok(RowLimit { offset: 0, quantity, with_ties: top.with_ties })
+    }
+
+    fn is_unlimited(&self) -> bool {
+        self.offset == 0 && self.quantity.is_none()
+    }
+
+    /**
+     * The number of rows that have to be read before the limit is reached, if it is known before all the rows are read
+    **/
+    fn scan_limit(&self) -> Option<usize> {
+        match self.quantity {
+            Some(Quantity::Rows(quantity)) if !self.with_ties => { Some(self.offset.saturating_add(quantity)) }
+            _ => { None }
+        }
+    }
+}
+
+fn evaluate_row_count(expr: &Expr, context: &QueryContext) -> Result<usize, CoreError> {
+    match evaluate_expr(expr, Scope::Row(&context.outer_row()), context)?.to_f64() {
+        Some(count) if count >= 0.0 && count.fract() == 0.0 => { Ok(count as usize) }
+        _ => { Err(CoreError::GeneralError(format!("The row count {} must be a positive whole number", expr))) }
+    }
+}
+
+fn evaluate_percent(expr: &Expr, context: &QueryContext) -> Result<f64, CoreError> {
+    match evaluate_expr(expr, Scope::Row(&context.outer_row()), context)?.to_f64() {
+        Some(percent) if (0.0..=100.0).contains(&percent) => { Ok(percent) }
+        _ => { Err(CoreError::GeneralError(format!("The percent {} must be between 0 and 100", expr))) }
+    }
+}
+
+type KeyedRow = (/*order by keys*/ Vec<CoreValue>, /*selected values*/ Vec<CoreValue>);
+
+/**
+ * Sorts the rows by their ORDER BY keys
+ * 
+ * Ties are broken by the selected values so that the order does not depend on the order the directory was read in
+**/
+fn order_rows(mut rows: Vec<KeyedRow>, order_by: &[OrderByExpr], time_zone: TimeZoneSetting) -> Result<Vec<KeyedRow>, CoreError> {
+    let mut error = None;
+    rows.sort_by(|(left_keys, left_row), (right_keys, right_row)| {
+        for ((left, right), order) in left_keys.iter().zip(right_keys).zip(order_by) {
+            let (asc, nulls_first) = order_direction(order);
+            match compare_order_keys(left, right, asc, nulls_first, time_zone) {
+                Ok(Ordering::Equal) => { continue }
+                Ok(ordering) => { return ordering }
+                Err(e) => { error.get_or_insert(e); return Ordering::Equal }
+            }
+        }
+
+        left_row.iter().zip(right_row)
+            .map(|(left, right)| compare_order_keys(left, right, true, true, time_zone).unwrap_or(Ordering::Equal))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+
+    match error {
+        Some(e) => { Err(e) }
+        None => { Ok(rows) }
+    }
+}
+
+/**
+ * Applies OFFSET and then TOP, LIMIT or FETCH to rows that have already been sorted
+ * 
+ * WITH TIES keeps any following rows whose ORDER BY keys equal those of the last row
+**/
+fn limit_rows(mut rows: Vec<KeyedRow>, row_limit: &RowLimit, order_by: &[OrderByExpr], time_zone: TimeZoneSetting) -> Vec<KeyedRow> {
+    let total = rows.len();
+    let mut rows = rows.split_off(row_limit.offset.min(total));
+
+    let mut quantity = match row_limit.quantity {
+        Some(Quantity::Rows(quantity)) => { quantity.min(rows.len()) }
+        Some(Quantity::Percent(percent)) => { ((total as f64 * percent / 100.0).ceil() as usize).min(rows.len()) }
+        None => { return rows }
+    };
+
+    if row_limit.with_ties && quantity > 0 {
+        let is_tie = |(left, _): &KeyedRow, (right, _): &KeyedRow| left.iter().zip(right).zip(order_by).all(|((left, right), order)| {
+            let (asc, nulls_first) = order_direction(order);
+            matches!(compare_order_keys(left, right, asc, nulls_first, time_zone), Ok(Ordering::Equal))
+        });
+
+        while quantity < rows.len() && is_tie(&rows[quantity - 1], &rows[quantity]) {
+            quantity += 1;
+        }
+    }
+
+    rows.truncate(quantity);
+    rows
+}
+
+/**
+ * NULLs sort as the lowest value unless NULLS FIRST or NULLS LAST is given
+**/
+fn order_direction(order: &OrderByExpr) -> (/*asc*/ bool, /*nulls_first*/ bool) {
+    let asc = order.asc.unwrap_or(true);
+    (asc, order.nulls_first.unwrap_or(asc))
+}
+
+fn compare_order_keys(left: &CoreValue, right: &CoreValue, asc: bool, nulls_first: bool, time_zone: TimeZoneSetting) -> Result<Ordering, CoreError> {
+    Ok(match (left, right) {
+        (CoreValue::Null, CoreValue::Null) => { Ordering::Equal }
+        (CoreValue::Null, _) => { if nulls_first { Ordering::Less } else { Ordering::Greater } }
+        (_, CoreValue::Null) => { if nulls_first { Ordering::Greater } else { Ordering::Less } }
+        _ => {
+            let ordering = compare_values(left, right, time_zone)?.unwrap_or(Ordering::Equal);
+            if asc { ordering } else { ordering.reverse() }
+        }
+    })
+}
+
+/**
+ * Resolves an ORDER BY or GROUP BY expression that refers to the select list by ordinal position (ORDER BY 2) or by alias (ORDER BY kb)
+**/
+fn resolve_select_list_expr(expr: &Expr, projection: &[SelectItem], tables: &[TableSchema]) -> Result<Expr, CoreError> {
+    match expr {
+        Expr::Value(Value::Number(position, _)) => {
+            let select_exprs = projection_exprs(projection, tables)?;
+            match position.parse::<usize>() {
+                Ok(position) if position >= 1 && position <= select_exprs.len() => { Ok(select_exprs[position - 1].1.clone()) }
+                _ => { Err(CoreError::GeneralError(format!("The position {} is not in the select list", position))) }
+            }
+        }
+        Expr::Identifier(ident) => {
+            let aliased = projection.iter().find_map(|item| match item {
+                SelectItem::ExprWithAlias { expr, alias } if alias.value.eq_ignore_ascii_case(&ident.value) => { Some(expr.clone()) }
+                _ => { None }
+            });
+
+            Ok(aliased.unwrap_or_else(|| expr.clone()))
+        }
+        _ => { Ok(expr.clone()) }
+    }
+}
+
+/**
+ * The column name and expression of each item in the select list, with * expanded to every column
+ * 
+ * Columns are named by their alias, otherwise by the column or expression as it was written
+**/
+fn projection_exprs(projection: &[SelectItem], tables: &[TableSchema]) -> Result<Vec<(String, Expr)>, CoreError> {
+    let mut exprs = Vec::new();
+    for item in projection {
+        match item {
+            SelectItem::UnnamedExpr(expr) => {
+                let name = match expr {
+                    Expr::Identifier(ident) => { ident.value.clone() }
+                    Expr::CompoundIdentifier(idents) if !idents.is_empty() => { idents[idents.len() - 1].value.clone() }
+                    _ => { expr.to_string() }
+                };
+
+                exprs.push((name, expr.clone()));
+            }
+            SelectItem::ExprWithAlias { expr, alias } => {
+                if projection.iter().filter(|item| matches!(item, SelectItem::ExprWithAlias { alias: other, .. } if other.value.eq_ignore_ascii_case(&alias.value))).count() > 1 {
+                    return Err(CoreError::GeneralError(format!("The column alias '{}' is specified more than once in the select list", alias.value)));
+                }
+
+                exprs.push((alias.value.clone(), expr.clone()))
+            }
+            SelectItem::Wildcard(_) => {
+                for table in tables {
+                    exprs.extend(table_columns(table));
+                }
+            }
+            SelectItem::QualifiedWildcard(name, _) => {
+                let qualifier = name.0.iter().map(|ident| ident.value.as_str()).collect::<Vec<&str>>().join(".");
+                match tables.iter().find(|table| table.name.eq_ignore_ascii_case(&qualifier)) {
+                    Some(table) => { exprs.extend(table_columns(table)) }
+                    None => { return Err(CoreError::GeneralError(format!("The table '{}' in {}.* is not in the FROM clause", qualifier, qualifier))) }
+                }
+            }
+        }
+    }
+
+    Ok(exprs)
+}
+
+/**
+ * Every column of a table, qualified by the table's name
+**/
+fn table_columns(table: &TableSchema) -> impl Iterator<Item = (String, Expr)> + '_ {
+    table.column_names().into_iter().map(move |column| (column.clone(), Expr::CompoundIdentifier(vec![Ident::new(&table.name), Ident::new(column)])))
+}
+
+/**
+ * The rows an expression is evaluated against
+ * 
+ * Aggregate functions are evaluated over a group, any other column must be part of the group's GROUP BY
+**/
+#[derive(Clone, Copy)]
+enum Scope<'a> {
+    Row(&'a Row),
+    Group(&'a [Row], /*group by*/ &'a [Expr], /*tables*/ &'a [TableSchema]),
+}
+
+/**
+ * Evaluates a SQL expression against a single row or a group of rows
+**/
+fn evaluate_expr(expr: &Expr, scope: Scope, context: &QueryContext) -> Result<CoreValue, CoreError> {
+    if let Scope::Group(rows, group_by, tables) = scope {
+        if group_by.iter().any(|group| is_same_column(group, expr, tables)) {
+            return match rows.first() {
+                Some(row) => { evaluate_expr(expr, Scope::Row(row), context) }
+                None => { Ok(CoreValue::Null) }
+            };
+        }
+    }
+
+    match expr {
+        Expr::Identifier(ident) => {
+            match scope {
+                Scope::Row(row) => { row.column(None, &ident.value) }
+                Scope::Group(..) => { Err(CoreError::GeneralError(format!("The column {} must be in the GROUP BY clause or used in an aggregate function", expr))) }
+            }
+        }
+        Expr::CompoundIdentifier(idents) => {
+            match (scope, idents.as_slice()) {
+                (Scope::Row(row), [table_name, column_name]) => { row.column(Some(&table_name.value), &column_name.value) }
+                (Scope::Row(_), _) => { Err(CoreError::GeneralError(format!("The column {} must be qualified by a single table name", expr))) }
+                (Scope::Group(..), _) => { Err(CoreError::GeneralError(format!("The column {} must be in the GROUP BY clause or used in an aggregate function", expr))) }
+            }
+        }
+        Expr::Value(value) => { CoreValue::from_literal(value) }
+        Expr::Interval(interval) => { CoreValue::from_interval(&evaluate_expr(&interval.value, scope, context)?, interval.leading_field.as_ref()) }
+        Expr::Nested(expr) => { evaluate_expr(expr, scope, context) }
+        Expr::UnaryOp { op: UnaryOperator::Not, expr } => {
+            match evaluate_expr(expr, scope, context)? {
+                CoreValue::Boolean(b) => { Ok(CoreValue::Boolean(!b)) }
+                CoreValue::Null => { Ok(CoreValue::Null) }
+                _ => { Err(CoreError::GeneralError(format!("The expression {} is not a boolean", expr))) }
+            }
+        }
+        Expr::UnaryOp { op, expr } => {
+            match (op, evaluate_expr(expr, scope, context)?) {
+                (_, CoreValue::Null) => { Ok(CoreValue::Null) }
+                (UnaryOperator::Minus, CoreValue::Integer(integer)) => { integer.checked_neg().map(CoreValue::Integer).ok_or_else(|| CoreError::GeneralError(format!("Arithmetic overflow error for {}", expr))) }
+                (UnaryOperator::Minus, CoreValue::Float(float)) => { Ok(CoreValue::Float(-float)) }
+                (UnaryOperator::Plus, value @ CoreValue::Integer(_)) | (UnaryOperator::Plus, value @ CoreValue::Float(_)) => { Ok(value) }
+                _ => { Err(CoreError::GeneralError(format!("The expression {} is not a number", expr))) }
+            }
+        }
+        Expr::BinaryOp { left, op, right } => {
+            let left = evaluate_expr(left, scope, context)?;
+            let right = evaluate_expr(right, scope, context)?;
+
+            consume_op(left, op, right, context.options.time_zone)
+        }
+        Expr::Function(function) => { evaluate_function(function, scope, context) }
+        Expr::Like { negated, any: false, expr: value, pattern, escape_char } | Expr::ILike { negated, any: false, expr: value, pattern, escape_char } => {
+            let case_insensitive = matches!(expr, Expr::ILike { .. });
+            match_pattern(&evaluate_expr(value, scope, context)?, &evaluate_expr(pattern, scope, context)?, |pattern| Pattern::like(pattern, escape_char.as_deref(), case_insensitive, context.options.like_bracket_classes), context.options.time_zone)
+                .map(|is_match| negate_if(is_match, *negated))
+        }
+        // IN is true when the value equals any item, otherwise it is NULL if the value or any item is NULL
+        Expr::InList { expr: value, list, negated } => {
+            let value = evaluate_expr(value, scope, context)?;
+            let mut result = CoreValue::Boolean(false);
+            for item in list {
+                match consume_op(value.clone(), &BinaryOperator::Eq, evaluate_expr(item, scope, context)?, context.options.time_zone)? {
+                    CoreValue::Boolean(true) => { result = CoreValue::Boolean(true); break }
+                    CoreValue::Null => { result = CoreValue::Null }
+                    _ => {}
+                }
+            }
+
+            Ok(negate_if(result, *negated))
+        }
+        Expr::Between { expr: value, negated, low, high } => {
+            let value = evaluate_expr(value, scope, context)?;
+            let above_low = consume_op(value.clone(), &BinaryOperator::GtEq, evaluate_expr(low, scope, context)?, context.options.time_zone)?;
+            let below_high = consume_op(value, &BinaryOperator::LtEq, evaluate_expr(high, scope, context)?, context.options.time_zone)?;
+
+            consume_op(above_low, &BinaryOperator::And, below_high, context.options.time_zone).map(|is_between| negate_if(is_between, *negated))
+        }
+        Expr::InSubquery { expr: value, subquery, negated } => {
+            let value = evaluate_expr(value, scope, context)?;
+            let mut result = CoreValue::Boolean(false);
+            for item in evaluate_subquery_column(subquery, scope, context)? {
+                match consume_op(value.clone(), &BinaryOperator::Eq, item, context.options.time_zone)? {
+                    CoreValue::Boolean(true) => { result = CoreValue::Boolean(true); break }
+                    CoreValue::Null => { result = CoreValue::Null }
+                    _ => {}
+                }
+            }
+
+            Ok(negate_if(result, *negated))
+        }
+        Expr::Exists { subquery, negated } => {
+            let result_set = consume_query((**subquery).clone(), &context.with_outer(outer_row(scope)))?;
+
+            Ok(CoreValue::Boolean(result_set.rows.is_empty() == *negated))
+        }
+        // A scalar subquery is NULL when it returns no rows
+        Expr::Subquery(subquery) => {
+            let mut values = evaluate_subquery_column(subquery, scope, context)?;
+            if values.len() > 1 {
+                return Err(CoreError::GeneralError(format!("The subquery {} returned more than 1 value, which is not allowed when it is used as an expression", subquery)));
+            }
+
+            Ok(values.pop().unwrap_or(CoreValue::Null))
+        }
+        Expr::IsNull(value) => { Ok(CoreValue::Boolean(evaluate_expr(value, scope, context)?.is_null())) }
+        Expr::IsNotNull(value) => { Ok(CoreValue::Boolean(!evaluate_expr(value, scope, context)?.is_null())) }
+        Expr::RLike { negated, expr: value, pattern, .. } => {
+            match_pattern(&evaluate_expr(value, scope, context)?, &evaluate_expr(pattern, scope, context)?, Pattern::regexp, context.options.time_zone)
+                .map(|is_match| negate_if(is_match, *negated))
+        }
+        // A simple CASE compares its operand to each WHEN value, a searched CASE evaluates each WHEN condition
+        Expr::Case { operand, conditions, results, else_result } => {
+            let operand = match operand { Some(operand) => { Some(evaluate_expr(operand, scope, context)?) } None => { None } };
+            for (condition, result) in conditions.iter().zip(results) {
+                let is_match = match &operand {
+                    Some(operand) => { compare_values(operand, &evaluate_expr(condition, scope, context)?, context.options.time_zone)? == Some(Ordering::Equal) }
+                    None => { evaluate_predicate(condition, scope, context)? }
+                };
+
+                if is_match {
+                    return evaluate_expr(result, scope, context);
+                }
+            }
+
+            match else_result {
+                Some(else_result) => { evaluate_expr(else_result, scope, context) }
+                None => { Ok(CoreValue::Null) }
+            }
+        }
+        _ => { Err(CoreError::GeneralError(format!("The expression {} is not supported", expr))) }
+    }
+}
+
+/**
+ * Executes a subquery that selects a single column, such as the subquery of IN or a scalar subquery, for the current row
+**/
+fn evaluate_subquery_column(subquery: &Query, scope: Scope, context: &QueryContext) -> Result<Vec<CoreValue>, CoreError> {
+    let result_set = consume_query(subquery.clone(), &context.with_outer(outer_row(scope)))?;
+    if result_set.columns.len() != 1 {
+        return Err(CoreError::GeneralError(format!("Only one expression can be specified in the select list of the subquery {}", subquery)));
+    }
+
+    Ok(result_set.rows.into_iter().flat_map(|row| row.into_iter().next()).collect())
+}
+
+/**
+ * The row a correlated subquery can reference, a group is referenced by its first row as its GROUP BY columns are the same in each row
+**/
+fn outer_row(scope: Scope) -> Option<Rc<Row>> {
+    match scope {
+        Scope::Row(row) => { Some(Rc::new(row.clone())) }
+        Scope::Group(rows, ..) => { rows.first().map(|row| Rc::new(row.clone())) }
+    }
+}
+
+/**
+ * Matches a value against a LIKE, GLOB or REGEXP pattern, NULL is returned if either is NULL
+ * 
+ * Names and paths are matched by their raw bytes, other values by how they are displayed
+**/
+fn match_pattern(value: &CoreValue, pattern: &CoreValue, compile: impl FnOnce(&str) -> Result<Pattern, CoreError>, time_zone: TimeZoneSetting) -> Result<CoreValue, CoreError> {
+    if value.is_null() || pattern.is_null() {
+        return Ok(CoreValue::Null);
+    }
+
+    let pattern = match pattern.to_bytes().map(std::str::from_utf8) {
+        Some(Ok(pattern)) => { compile(pattern)? }
+        Some(Err(_)) => { return Err(CoreError::GeneralError(format!("The pattern {} is not valid UTF-8", pattern))) }
+        None => { compile(&pattern.display(time_zone))? }
+    };
+
+    Ok(CoreValue::Boolean(match value.to_bytes() {
+        Some(bytes) => { pattern.is_match(bytes) }
+        None => { pattern.is_match(value.display(time_zone).as_bytes()) }
+    }))
+}
+
+fn negate_if(value: CoreValue, negated: bool) -> CoreValue {
+    match value {
+        CoreValue::Boolean(b) => { CoreValue::Boolean(b != negated) }
+        value => { value }
+    }
+}
+
+/**
+ * Evaluates a SQL expression as a condition, NULL is treated as false
+**/
+fn evaluate_predicate(expr: &Expr, scope: Scope, context: &QueryContext) -> Result<bool, CoreError> {
+    match evaluate_expr(expr, scope, context)? {
+        CoreValue::Boolean(b) => { Ok(b) }
+        CoreValue::Null => { Ok(false) }
+        _ => { Err(CoreError::GeneralError(format!("The expression {} is not a boolean", expr))) }
+    }
+}
+
+/**
+ * Evaluates an aggregate function over the rows of a group
+ * 
+ * COUNT(*) counts every row, any other argument is evaluated against each row and NULLs are ignored
+**/
+fn evaluate_function(function: &Function, scope: Scope, context: &QueryContext) -> Result<CoreValue, CoreError> {
+    let aggregate = Aggregate::from_str(&function.name.to_string())?;
+    if function.over.is_some() {
+        return Err(CoreError::GeneralError(format!("Window functions such as {} are not supported", function)));
+    }
+
+    let rows = match scope {
+        Scope::Group(rows, ..) => { rows }
+        Scope::Row(_) => { return Err(CoreError::GeneralError(format!("The aggregate function {} is not allowed here", function))) }
+    };
+
+    let (args, distinct) = match &function.args {
+        FunctionArguments::List(list) if list.clauses.is_empty() => { (&list.args[..], list.duplicate_treatment == Some(DuplicateTreatment::Distinct)) }
+        _ => { return Err(CoreError::GeneralError(format!("The arguments of the aggregate function {} are not supported", function))) }
+    };
+
+    let values = match args {
+        [FunctionArg::Unnamed(FunctionArgExpr::Wildcard)] if aggregate == Aggregate::Count && !distinct => { rows.iter().map(|_| CoreValue::Boolean(true)).collect() }
+        [FunctionArg::Unnamed(FunctionArgExpr::Expr(arg))] => { rows.iter().map(|row| evaluate_expr(arg, Scope::Row(row), context)).collect::<Result<Vec<CoreValue>, CoreError>>()? }
+        _ => { return Err(CoreError::GeneralError(format!("The aggregate function {} takes a single argument", function))) }
+    };
+
+    aggregate.aggregate(values, distinct, context.options.time_zone)
+}
+
+/**
+ * Consumes and executes a SQL operation
+**/
+fn consume_op(left: CoreValue, op: &BinaryOperator, right: CoreValue, time_zone: TimeZoneSetting) -> Result<CoreValue, CoreError> {
+    //println!("op: {{ left: {:?}, right: {:?} }}", left, right);
+    match op {
+        BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => { consume_arithmetic(left, op, right) }
+        BinaryOperator::BitwiseAnd | BinaryOperator::BitwiseOr | BinaryOperator::BitwiseXor => {
+            match (left, right) {
+                (CoreValue::Null, _) | (_, CoreValue::Null) => { Ok(CoreValue::Null) }
+                (CoreValue::Integer(a), CoreValue::Integer(b)) => {
+                    Ok(CoreValue::Integer(match op {
+                        BinaryOperator::BitwiseAnd => { a & b }
+                        BinaryOperator::BitwiseOr => { a | b }
+                        _ => { a ^ b }
+                    }))
+                }
+                _ => { Err(invalid_types()) }
+            }
+        }
+        // Values are concatenated as they are displayed, names keep their raw bytes
+        BinaryOperator::StringConcat => {
+            match (&left, &right) {
+                (CoreValue::Null, _) | (_, CoreValue::Null) => { Ok(CoreValue::Null) }
+                _ => {
+                    let mut bytes = left.to_bytes().map(|bytes| bytes.to_vec()).unwrap_or_else(|| left.display(time_zone).into_bytes());
+                    bytes.extend_from_slice(&right.to_bytes().map(|bytes| bytes.to_vec()).unwrap_or_else(|| right.display(time_zone).into_bytes()));
+
+                    Ok(bytes_value(bytes))
+                }
+            }
+        }
+        BinaryOperator::Custom(custom) if custom == dialect::GLOB_OPERATOR => { match_pattern(&left, &right, Pattern::glob, time_zone) }
+        BinaryOperator::Eq | BinaryOperator::NotEq | BinaryOperator::Gt | BinaryOperator::Lt | BinaryOperator::GtEq | BinaryOperator::LtEq => {
+            Ok(match compare_values(&left, &right, time_zone)? {
+                Some(ordering) => { CoreValue::Boolean(match op {
+                    BinaryOperator::Eq => { ordering == Ordering::Equal }
+                    BinaryOperator::NotEq => { ordering != Ordering::Equal }
+                    BinaryOperator::Gt => { ordering == Ordering::Greater }
+                    BinaryOperator::Lt => { ordering == Ordering::Less }
+                    BinaryOperator::GtEq => { ordering != Ordering::Less }
+                    _ => { ordering != Ordering::Greater }
+                })}
+                None => { CoreValue::Null }
+            })
+        }
+        // Three-valued logic, NULL is unknown rather than false, both sides must be booleans or NULL
+        BinaryOperator::And => {
+            match (left, right) {
+                (CoreValue::Boolean(a), CoreValue::Boolean(b)) => { Ok(CoreValue::Boolean(a && b)) }
+                (CoreValue::Boolean(false), CoreValue::Null) | (CoreValue::Null, CoreValue::Boolean(false)) => { Ok(CoreValue::Boolean(false)) }
+                (CoreValue::Boolean(_) | CoreValue::Null, CoreValue::Boolean(_) | CoreValue::Null) => { Ok(CoreValue::Null) }
+                _ => { Err(invalid_types()) }
+            }
+        }
+        BinaryOperator::Or => {
+            match (left, right) {
+                (CoreValue::Boolean(a), CoreValue::Boolean(b)) => { Ok(CoreValue::Boolean(a || b)) }
+                (CoreValue::Boolean(true), CoreValue::Null) | (CoreValue::Null, CoreValue::Boolean(true)) => { Ok(CoreValue::Boolean(true)) }
+                (CoreValue::Boolean(_) | CoreValue::Null, CoreValue::Boolean(_) | CoreValue::Null) => { Ok(CoreValue::Null) }
+                _ => { Err(invalid_types()) }
+            }
+        }
+        _ => { Err(CoreError::GeneralError(format!("The operator {} is not supported", op))) }
+    }
+}
+
+/**
+ * Adds, subtracts, multiplies, divides or takes the remainder of two values, NULL is returned if either value is NULL
+ * 
+ * Integers that overflow and division by zero are errors rather than wrapping or panicking
+**/
+fn consume_arithmetic(left: CoreValue, op: &BinaryOperator, right: CoreValue) -> Result<CoreValue, CoreError> {
+    let overflow = || CoreError::GeneralError(format!("Arithmetic overflow error for the operator {}", op));
+    let divide_by_zero = || CoreError::GeneralError("Divide by zero error encountered".to_owned());
+    match (left, op, right) {
+        (CoreValue::Null, _, _) | (_, _, CoreValue::Null) => { Ok(CoreValue::Null) }
+        (CoreValue::Integer(a), _, CoreValue::Integer(b)) => {
+            match op {
+                BinaryOperator::Plus => { a.checked_add(b) }
+                BinaryOperator::Minus => { a.checked_sub(b) }
+                BinaryOperator::Multiply => { a.checked_mul(b) }
+                _ if b == 0 => { return Err(divide_by_zero()) }
+                BinaryOperator::Divide => { a.checked_div(b) }
+                _ => { a.checked_rem(b) }
+            }.map(CoreValue::Integer).ok_or_else(overflow)
+        }
+        (left @ CoreValue::Integer(_), _, right @ CoreValue::Float(_)) | (left @ CoreValue::Float(_), _, right @ CoreValue::Integer(_)) | (left @ CoreValue::Float(_), _, right @ CoreValue::Float(_)) => {
+            let (a, b) = (left.to_f64().unwrap_or_default(), right.to_f64().unwrap_or_default());
+            let result = match op {
+                BinaryOperator::Plus => { a + b }
+                BinaryOperator::Minus => { a - b }
+                BinaryOperator::Multiply => { a * b }
+                _ if b == 0.0 => { return Err(divide_by_zero()) }
+                BinaryOperator::Divide => { a / b }
+                _ => { a % b }
+            };
+
+            if result.is_finite() { Ok(CoreValue::Float(result)) } else { Err(overflow()) }
+        }
+        (CoreValue::String(mut a), BinaryOperator::Plus, CoreValue::String(b)) => { a.push_str(&b); Ok(CoreValue::String(a)) }
+        (CoreValue::Timestamp(a), BinaryOperator::Plus, CoreValue::Duration(b)) | (CoreValue::Duration(b), BinaryOperator::Plus, CoreValue::Timestamp(a)) => {
+            a.checked_add(b).map(CoreValue::Timestamp).ok_or_else(overflow)
+        }
+        (CoreValue::Timestamp(a), BinaryOperator::Minus, CoreValue::Duration(b)) => { a.checked_sub(b).map(CoreValue::Timestamp).ok_or_else(overflow) }
+        (CoreValue::Timestamp(a), BinaryOperator::Minus, CoreValue::Timestamp(b)) => {
+            a.duration_since(b).map(CoreValue::Duration).map_err(|_| CoreError::GeneralError("A timestamp can only be subtracted from a later timestamp".to_owned()))
+        }
+        (CoreValue::Duration(a), BinaryOperator::Plus, CoreValue::Duration(b)) => { a.checked_add(b).map(CoreValue::Duration).ok_or_else(overflow) }
+        (CoreValue::Duration(a), BinaryOperator::Minus, CoreValue::Duration(b)) => { a.checked_sub(b).map(CoreValue::Duration).ok_or_else(overflow) }
+        _ => { Err(invalid_types()) }
+    }
+}
+
+/**
+ * Lazily reads the files of a directory
+**/
+fn scan_table_name(table_name: &str) -> Result<impl Iterator<Item = CoreFile>, CoreError> {
+    Ok(fs::read_dir(table_name)?.flatten().map(CoreFile::from))
+}
+
+/**
+ * Reads a single table's rows, filtering them by the WHERE clause as they are read
+ * 
+ * The directory read stops once scan_limit rows have been selected
+**/
+fn scan_relation(relation: &TableFactor, schema: &TableSchema, selection: Option<&Expr>, scan_limit: Option<usize>, context: &QueryContext) -> Result<Vec<Row>, CoreError> {
+    let mut rows = Vec::new();
+    for row in relation_rows(relation, schema, &context.outer_row(), context)? {
+        if scan_limit.is_some_and(|scan_limit| rows.len() >= scan_limit) {
+            break;
+        }
+
+        let row = row.with_outer(context.outer.clone());
+        if match selection { Some(selection) => { evaluate_predicate(selection, Scope::Row(&row), context)? } None => { true } } {
+            rows.push(row);
+        }
+    }
+
+    Ok(rows)
+}
+
+/**
+ * Lazily reads the rows of a table, which is a common table expression in scope, a directory read from a column of the row, or a directory
+**/
+fn relation_rows(relation: &TableFactor, schema: &TableSchema, row: &Row, context: &QueryContext) -> Result<Box<dyn Iterator<Item = Row>>, CoreError> {
+    let name = match relation {
+        TableFactor::Table { name, .. } => { name }
+        _ => { return Err(CoreError::GeneralError(format!("The table {} is not supported", relation))) }
+    };
+
+    if let (Some(result_set), Some(columns)) = (relation_cte(relation, context), schema.columns.clone()) {
+        let table_name = schema.name.clone();
+        return Ok(Box::new((0..result_set.rows.len()).map(move |i| Row::values(&table_name, columns.clone(), result_set.rows[i].clone()))));
+    }
+
+    let table_name = schema.name.clone();
+    if is_lateral(relation) {
+        // A column that is NULL or is not a directory has no files
+        let directory = match evaluate_expr(&Expr::CompoundIdentifier(name.0.clone()), Scope::Row(row), context)?.to_path() {
+            Some(directory) if directory.is_dir() => { directory }
+            _ => { return Ok(Box::new(std::iter::empty())) }
+        };
+
+        // The directory keeps the root of the table it was read from, so that a walk of a directory tree has the depth below the queried directory
+        let root = match &schema.root {
+            Some(root) if directory.starts_with(root) => { Some(root.clone()) }
+            _ => { Some(directory.clone()) }
+        };
+
+        return Ok(Box::new(fs::read_dir(directory)?.flatten().map(move |entry| Row::new(&table_name, CoreFile { root: root.clone(), ..CoreFile::from(entry) }.columns()))));
+    }
+
+    match name.0.first() {
+        Some(directory) => { Ok(Box::new(scan_table_name(&directory.value)?.map(move |file| Row::new(&table_name, file.columns())))) }
+        None => { Err(CoreError::GeneralError("A table name is required".to_owned())) }
+    }
+}
+
+/**
+ * A table named by a column of another table, such as t.Path, reads the directory in that column
+**/
+fn is_lateral(relation: &TableFactor) -> bool {
+    matches!(relation, TableFactor::Table { name, .. } if name.0.len() == 2)
+}
+
+fn relation_cte(relation: &TableFactor, context: &QueryContext) -> Option<Rc<ResultSet>> {
+    relation_cte_with_root(relation, context).map(|(result_set, _)| result_set)
+}
+
+fn relation_cte_with_root(relation: &TableFactor, context: &QueryContext) -> Option<(Rc<ResultSet>, Option<PathBuf>)> {
+    match relation {
+        TableFactor::Table { name, .. } if name.0.len() == 1 => { context.find_cte_with_root(&name.0[0].value) }
+        _ => { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH_TO_TEST_DIR: &str = "./test/";
+
+    #[test]
+    fn consume_op_add_number_number() {
+        let left_val = CoreValue::Integer(2);
+        let right_val = CoreValue::Integer(2);
+        let result = consume_op(left_val, &BinaryOperator::Plus, right_val, TimeZoneSetting::Utc);
+
+        assert_eq!(match result {
+            Ok(CoreValue::Integer(integer)) => { integer }
+            _ => { panic!("Incorrect enum variant expected") }
+        }, 4);
+    }
+
+    #[test]
+    fn consume_op_add_string_string() {
+        let left_val = CoreValue::String("2".to_owned());
+        let right_val = CoreValue::String("2".to_owned());
+        let result = consume_op(left_val, &BinaryOperator::Plus, right_val, TimeZoneSetting::Utc);
+
+        assert_eq!(match result {
+            Ok(CoreValue::String(string)) => { string }
+            _ => { panic!("Incorrect enum variant expected") }
+        }, "22".to_owned());
+    }
+
+    #[test]
+    fn consume_op_subtract_string_string() {
+        let left_val = CoreValue::String("2".to_owned());
+        let right_val = CoreValue::String("2".to_owned());
+        let result = consume_op(left_val, &BinaryOperator::Minus, right_val, TimeZoneSetting::Utc);
+
+        assert!(match result {
+            Err(CoreError::GeneralError(_)) => { true }
+            _ => { panic!("Incorrect enum variant expected") }
+        });
+    }
+
+    #[test]
     fn consume_op_subtract_number_number() {
-        let left_val = Value::Number("-2".to_owned());
-        let right_val = Value::Number("-2".to_owned());
-        let result = consume_op(left_val, &BinaryOperator::Minus, right_val);
+        let left_val = CoreValue::Integer(-2);
+        let right_val = CoreValue::Integer(-2);
+        let result = consume_op(left_val, &BinaryOperator::Minus, right_val, TimeZoneSetting::Utc);
 
         assert_eq!(match result {
-            Ok(Value::Number(string)) => { string }
+            Ok(CoreValue::Integer(integer)) => { integer }
+            _ => { panic!("Incorrect enum variant expected") }
+        }, 0);
+    }
+
+    #[test]
+    fn consume_op_large_numbers() {
+        let size = CoreValue::from(FileColumn::Size(Some(3_000_000_000)));
+
+        assert_eq!(consume_op(size.clone(), &BinaryOperator::Plus, size.clone(), TimeZoneSetting::Utc), Ok(CoreValue::Integer(6_000_000_000)));
+        assert_eq!(consume_op(size, &BinaryOperator::Divide, CoreValue::Float(2.0), TimeZoneSetting::Utc), Ok(CoreValue::Float(1.5e9)));
+        assert!(consume_op(CoreValue::Integer(i64::MAX), &BinaryOperator::Plus, CoreValue::Integer(1), TimeZoneSetting::Utc).is_err());
+        assert_eq!(consume_op(CoreValue::Integer(1), &BinaryOperator::Plus, CoreValue::Null, TimeZoneSetting::Utc), Ok(CoreValue::Null));
+    }
+
+    #[test]
+    fn consume_op_operators() {
+        assert_eq!(consume_op(CoreValue::Integer(7), &BinaryOperator::Modulo, CoreValue::Integer(4), TimeZoneSetting::Utc), Ok(CoreValue::Integer(3)));
+        assert!(consume_op(CoreValue::Integer(7), &BinaryOperator::Modulo, CoreValue::Integer(0), TimeZoneSetting::Utc).is_err());
+        assert_eq!(consume_op(CoreValue::Integer(0o755), &BinaryOperator::BitwiseAnd, CoreValue::Integer(0o022), TimeZoneSetting::Utc), Ok(CoreValue::Integer(0o000)));
+        assert_eq!(consume_op(CoreValue::Integer(0o644), &BinaryOperator::BitwiseOr, CoreValue::Integer(0o111), TimeZoneSetting::Utc), Ok(CoreValue::Integer(0o755)));
+        assert_eq!(consume_op(CoreValue::String("a".to_owned()), &BinaryOperator::StringConcat, CoreValue::Integer(1), TimeZoneSetting::Utc), Ok(CoreValue::String("a1".to_owned())));
+        assert_eq!(consume_op(CoreValue::String("a".to_owned()), &BinaryOperator::StringConcat, CoreValue::Null, TimeZoneSetting::Utc), Ok(CoreValue::Null));
+        assert_eq!(consume_op(CoreValue::Integer(1), &BinaryOperator::NotEq, CoreValue::Float(1.5), TimeZoneSetting::Utc), Ok(CoreValue::Boolean(true)));
+    }
+
+    #[test]
+    fn evaluate_expr_operators() {
+        let row = Row::new("./", vec![FileColumn::Name(Some(OsString::from("a.txt"))), FileColumn::Size(Some(8192))]);
+
+        assert_eq!(evaluate_expr(&parse_expr("Size * 100 / 1024"), Scope::Row(&row), &QueryContext::default()), Ok(CoreValue::Integer(800)));
+        assert_eq!(evaluate_expr(&parse_expr("-Size % 1000"), Scope::Row(&row), &QueryContext::default()), Ok(CoreValue::Integer(-192)));
+        assert_eq!(evaluate_expr(&parse_expr("Name || '.bak'"), Scope::Row(&row), &QueryContext::default()), Ok(CoreValue::String("a.txt.bak".to_owned())));
+        assert_eq!(evaluate_predicate(&parse_expr("Size >= 4096 AND Size <> 4096 AND NOT Size <= 4096"), Scope::Row(&row), &QueryContext::default()), Ok(true));
+        assert!(evaluate_expr(&parse_expr("Size / 0"), Scope::Row(&row), &QueryContext::default()).is_err());
+    }
+
+    #[test]
+    fn evaluate_expr_patterns() {
+        let row = Row::new("./", vec![FileColumn::Name(Some(OsString::from("Archive.tar.gz"))), FileColumn::Path(Some(std::path::PathBuf::from("./src/Archive.tar.gz"))), FileColumn::FileExtension(Some(OsString::from("gz")))]);
+        let is_match = |expr: &str| evaluate_expr(&parse_expr(expr), Scope::Row(&row), &QueryContext::default());
+
+        assert_eq!(is_match("Name LIKE '%.tar._z'"), Ok(CoreValue::Boolean(true)));
+        assert_eq!(is_match("Name LIKE 'archive%'"), Ok(CoreValue::Boolean(false)));
+        assert_eq!(is_match("Name ILIKE 'archive%'"), Ok(CoreValue::Boolean(true)));
+        assert_eq!(is_match("Name NOT ILIKE 'archive%'"), Ok(CoreValue::Boolean(false)));
+        assert_eq!(is_match("Path LIKE './src/%'"), Ok(CoreValue::Boolean(true)));
+        assert_eq!(is_match("Name GLOB '*.tar.gz'"), Ok(CoreValue::Boolean(true)));
+        assert_eq!(is_match("Name NOT GLOB '*.TAR.GZ'"), Ok(CoreValue::Boolean(true)));
+        assert_eq!(is_match("FileExtension REGEXP '^(gz|xz)$'"), Ok(CoreValue::Boolean(true)));
+        assert_eq!(is_match("Path NOT RLIKE '^\\./src/'"), Ok(CoreValue::Boolean(false)));
+        assert_eq!(is_match("NULL LIKE '%'"), Ok(CoreValue::Null));
+        assert!(is_match("Name REGEXP '('").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn evaluate_expr_patterns_non_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+        let row = Row::new("./", vec![FileColumn::Name(Some(OsString::from_vec(b"caf\xe9.txt".to_vec())))]);
+
+        assert_eq!(evaluate_expr(&parse_expr("Name LIKE 'caf_.txt'"), Scope::Row(&row), &QueryContext::default()), Ok(CoreValue::Boolean(true)));
+        assert_eq!(evaluate_expr(&parse_expr("Name GLOB '*.txt'"), Scope::Row(&row), &QueryContext::default()), Ok(CoreValue::Boolean(true)));
+    }
+
+    #[test]
+    fn evaluate_predicate_in_between_is_null() {
+        let row = Row::new("./", vec![FileColumn::FileExtension(Some(OsString::from("png"))), FileColumn::Size(Some(2048)), FileColumn::Created(None)]);
+        let evaluate = |expr: &str| evaluate_expr(&parse_expr(expr), Scope::Row(&row), &QueryContext::default());
+
+        assert_eq!(evaluate("FileExtension IN ('png', 'jpg') AND Size BETWEEN 1024 AND 10485760 AND Created IS NULL"), Ok(CoreValue::Boolean(true)));
+        assert_eq!(evaluate("FileExtension NOT IN ('gif', 'jpg')"), Ok(CoreValue::Boolean(true)));
+        assert_eq!(evaluate("FileExtension IN ('gif', NULL)"), Ok(CoreValue::Null));
+        assert_eq!(evaluate("FileExtension NOT IN ('gif', NULL)"), Ok(CoreValue::Null));
+        assert_eq!(evaluate("FileExtension IN ('png', NULL)"), Ok(CoreValue::Boolean(true)));
+        assert_eq!(evaluate("Size NOT BETWEEN 4096 AND 8192"), Ok(CoreValue::Boolean(true)));
+        assert_eq!(evaluate("Created BETWEEN 1 AND 2"), Ok(CoreValue::Null));
+        assert_eq!(evaluate("Size BETWEEN 4096 AND NULL"), Ok(CoreValue::Boolean(false)));
+        assert_eq!(evaluate("(Created IS NOT NULL)"), Ok(CoreValue::Boolean(false)));
+        assert_eq!(evaluate("Size IN (2048)"), Ok(CoreValue::Boolean(true)));
+    }
+
+    #[test]
+    fn consume_query_subqueries() {
+        let result = super::consume_query(parse_query("SELECT Name, (SELECT COUNT(*) FROM [./test/] d WHERE d.Type = f.Type) AS same_type FROM [./test/] f WHERE Name IN (SELECT Name FROM [./test/] WHERE Name <> 'two') ORDER BY Name"), &QueryContext::default()).expect("Subqueries are valid");
+        assert_eq!(result.rows, vec![vec![CoreValue::String("3.md".to_owned()), CoreValue::Integer(1)], vec![CoreValue::String("one".to_owned()), CoreValue::Integer(2)]]);
+
+        let result = super::consume_query(parse_query("SELECT Name FROM [./test/one/] f WHERE NOT EXISTS (SELECT 1 FROM [./test/two/] d WHERE d.Name = f.Name) AND EXISTS (SELECT * FROM [./test/two/] d WHERE d.FileExtension = f.FileExtension)"), &QueryContext::default()).expect("Subqueries are valid");
+        assert_eq!(first_column(result.rows), vec!["1.txt"]);
+
+        assert!(super::consume_query(parse_query("SELECT (SELECT Name FROM [./test/])"), &QueryContext::default()).is_err());
+        assert!(super::consume_query(parse_query("SELECT Name FROM [./test/] WHERE Name IN (SELECT Name, Size FROM [./test/])"), &QueryContext::default()).is_err());
+        assert_eq!(super::consume_query(parse_query("SELECT (SELECT Name FROM [./test/] WHERE Name = 'none')"), &QueryContext::default()).expect("Subquery is valid").rows, vec![vec![CoreValue::Null]]);
+    }
+
+    #[test]
+    fn consume_query_set_operations() {
+        let query_names = |sql: &str| super::consume_query(parse_query(sql), &QueryContext::default()).map(|result_set| first_column(result_set.rows));
+
+        assert_eq!(query_names("SELECT Name FROM [./test/] EXCEPT SELECT Name FROM [./test/] WHERE Type = 'dir'"), Ok(vec!["3.md".to_owned()]));
+        assert_eq!(query_names("SELECT Name FROM [./test/] INTERSECT SELECT Name FROM [./test/] WHERE Type = 'dir' ORDER BY Name DESC"), Ok(vec!["two".to_owned(), "one".to_owned()]));
+        assert_eq!(query_names("SELECT Name FROM [./test/one/] UNION SELECT Name FROM [./test/two/] UNION SELECT Name FROM [./test/one/] ORDER BY 1"), Ok(vec!["1.txt".to_owned(), "2.txt".to_owned()]));
+        assert_eq!(query_names("SELECT Type FROM [./test/] UNION ALL SELECT Type FROM [./test/one/] ORDER BY Type OFFSET 1 ROWS").map(|names| names.len()), Ok(3));
+        assert_eq!(query_names("SELECT Size FROM [./test/one/] UNION SELECT 0.5"), Ok(vec!["0".to_owned(), "0.5".to_owned()]));
+
+        assert!(query_names("SELECT Name, Size FROM [./test/] UNION SELECT Name FROM [./test/]").is_err());
+        assert!(query_names("SELECT Name FROM [./test/] UNION SELECT Size FROM [./test/]").is_err());
+        assert!(query_names("SELECT Name FROM [./test/] UNION SELECT Name FROM [./test/] ORDER BY Size").is_err());
+    }
+
+    #[test]
+    fn consume_query_ctes() {
+        let query_rows = |sql: &str| super::consume_query(parse_query(sql), &QueryContext::default()).map(|result_set| result_set.rows);
+
+        assert_eq!(query_rows("WITH dirs AS (SELECT Name FROM [./test/] WHERE Type = 'dir'), two (n) AS (SELECT Name FROM dirs WHERE Name LIKE 't%') SELECT d.Name, t.n FROM dirs d, two t ORDER BY d.Name"), Ok(vec![
+            vec![CoreValue::String("one".to_owned()), CoreValue::String("two".to_owned())],
+            vec![CoreValue::String("two".to_owned()), CoreValue::String("two".to_owned())],
+        ]));
+        assert_eq!(query_rows("WITH RECURSIVE r (n) AS (SELECT 1 UNION SELECT n + 1 FROM r WHERE n < 10) SELECT SUM(n) FROM r"), Ok(vec![vec![CoreValue::Integer(55)]]));
+        assert_eq!(query_rows("WITH RECURSIVE tree (path, depth) AS (SELECT Path, 0 FROM [./test/] UNION ALL SELECT c.Path, t.depth + 1 FROM tree t, t.path c) SELECT MAX(depth), COUNT(*) FROM tree"), Ok(vec![vec![CoreValue::Integer(1), CoreValue::Integer(5)]]));
+
+        // A directory read from a path of a common table expression keeps the depth below the directory the expression read
+        assert_eq!(query_rows("WITH RECURSIVE tree (Path, Depth) AS (SELECT Path, Depth FROM [./test/] UNION ALL SELECT c.Path, c.Depth FROM tree t, t.Path c) SELECT MAX(Depth) FROM tree"), Ok(vec![vec![CoreValue::Integer(2)]]));
+        assert_eq!(query_rows("WITH d (Path, RelativePath) AS (SELECT Path, 'one' FROM [./test/] WHERE Name = 'one') SELECT c.Depth, c.RelativePath FROM d, d.Path c"), Ok(vec![vec![CoreValue::Integer(2), CoreValue::Path(PathBuf::from("one/1.txt"))]]));
+
+        assert!(query_rows("WITH RECURSIVE r (n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM r) SELECT n FROM r").is_err());
+        assert!(query_rows("WITH a (x, y) AS (SELECT Name FROM [./test/]) SELECT x FROM a").is_err());
+        assert!(query_rows("WITH a AS (SELECT Name FROM [./test/]) SELECT Size FROM a").is_err());
+        // The common table expressions are out of scope once their query ends
+        assert!(query_rows("SELECT Name FROM a").is_err());
+        // A subquery can use the common table expressions of the query it is in
+        assert_eq!(query_rows("WITH dirs AS (SELECT Name FROM [./test/] WHERE Type = 'dir') SELECT COUNT(*) FROM [./test/] WHERE Name IN (SELECT Name FROM dirs)"), Ok(vec![vec![CoreValue::Integer(2)]]));
+    }
+
+    #[test]
+    fn consume_query_max_recursion() {
+        let sql = "WITH RECURSIVE r (n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM r WHERE n < 500) SELECT COUNT(*) FROM r";
+        let query_rows = |max_recursion: usize| super::consume_query(parse_query(sql), &QueryContext::new(QueryOptions { max_recursion, ..QueryOptions::default() })).map(|result_set| result_set.rows);
+
+        assert!(query_rows(QueryOptions::default().max_recursion).is_err());
+        assert!(query_rows(499).is_err());
+        assert_eq!(query_rows(500), Ok(vec![vec![CoreValue::Integer(500)]]));
+        assert_eq!(query_rows(0), Ok(vec![vec![CoreValue::Integer(500)]]));
+    }
+
+    #[test]
+    fn consume_statement_set_time_zone() {
+        let mut options = QueryOptions::default();
+        let mut set_time_zone = |sql: &str| {
+            let statement = Parser::parse_sql(&dialect::CoreDialect::from_str("mssql").expect("Dialect exists"), sql).expect("SQL is valid").remove(0);
+            consume_statement(statement, &mut options)
+        };
+
+        assert!(set_time_zone("SET TIME ZONE LOCAL").is_ok());
+        assert!(set_time_zone("SET TIME ZONE 'Mars'").is_err());
+        assert_eq!(options.time_zone, TimeZoneSetting::Local);
+        assert_eq!(QueryOptions::default().time_zone, TimeZoneSetting::Utc);
+    }
+
+    #[test]
+    fn consume_query_outer_joins() {
+        let query_rows = |sql: &str| super::consume_query(parse_query(sql), &QueryContext::default()).map(|result_set| result_set.rows);
+        let string = |str: &str| CoreValue::String(str.to_owned());
+
+        assert_eq!(query_rows("SELECT a.Name, b.Name FROM [./test/one/] a RIGHT JOIN [./test/] b ON a.Name = b.Name ORDER BY b.Name"), Ok(vec![
+            vec![CoreValue::Null, string("3.md")],
+            vec![CoreValue::Null, string("one")],
+            vec![CoreValue::Null, string("two")],
+        ]));
+        assert_eq!(query_rows("SELECT Name, a.Path, b.Path FROM [./test/one/] a FULL JOIN [./test/two/] b USING (Name) ORDER BY Name"), Ok(vec![
+            vec![string("1.txt"), CoreValue::Path(std::path::PathBuf::from("./test/one/1.txt")), CoreValue::Null],
+            vec![string("2.txt"), CoreValue::Null, CoreValue::Path(std::path::PathBuf::from("./test/two/2.txt"))],
+        ]));
+        assert_eq!(query_rows("SELECT COUNT(*) FROM [./test/] a CROSS JOIN [./test/] b"), Ok(vec![vec![CoreValue::Integer(9)]]));
+        // Directories have no extension, and a NULL key never matches
+        assert_eq!(query_rows("SELECT COUNT(*) FROM [./test/] a JOIN [./test/] b USING (FileExtension)"), Ok(vec![vec![CoreValue::Integer(1)]]));
+        assert_eq!(query_rows("WITH a (Name, Kind) AS (SELECT Name, Type FROM [./test/]), b (Name) AS (SELECT Name FROM [./test/] WHERE Type = 'dir') SELECT Name, a.Kind FROM a NATURAL JOIN b ORDER BY Name"), Ok(vec![
+            vec![string("one"), string("dir")],
+            vec![string("two"), string("dir")],
+        ]));
+        assert_eq!(query_rows("SELECT COUNT(*) FROM [./test/] a JOIN [./test/] b USING (Name)"), Ok(vec![vec![CoreValue::Integer(3)]]));
+        assert!(query_rows("SELECT a.Name FROM [./test/] a JOIN [./test/] b USING (Bogus)").is_err());
+    }
+
+    #[test]
+    fn consume_query_join_conditions() {
+        let query_rows = |sql: &str| super::consume_query(parse_query(sql), &QueryContext::default()).map(|result_set| result_set.rows);
+        let string = |str: &str| CoreValue::String(str.to_owned());
+
+        assert_eq!(query_rows("SELECT a.Name, b.Name FROM [./test/] a JOIN [./test/] b ON a.Type = b.Type AND a.Name <> b.Name ORDER BY a.Name"), Ok(vec![
+            vec![string("one"), string("two")],
+            vec![string("two"), string("one")],
+        ]));
+        assert_eq!(query_rows("SELECT a.Name, b.Name FROM [./test/] a LEFT JOIN [./test/] b ON (a.Type = b.Type) AND a.Name < b.Name ORDER BY a.Name"), Ok(vec![
+            vec![string("3.md"), CoreValue::Null],
+            vec![string("one"), string("two")],
+            vec![string("two"), CoreValue::Null],
+        ]));
+        // Without an equality every pair of rows is checked
+        assert_eq!(query_rows("SELECT COUNT(*) FROM [./test/] a JOIN [./test/] b ON a.Name < b.Name"), Ok(vec![vec![CoreValue::Integer(3)]]));
+        assert_eq!(query_rows("SELECT COUNT(a.Name), COUNT(b.Name), COUNT(*) FROM [./test/] a FULL JOIN [./test/] b ON a.Name > b.Name AND b.Type = 'file'"), Ok(vec![vec![CoreValue::Integer(3), CoreValue::Integer(4), CoreValue::Integer(5)]]));
+        // Equal values of different types match as they do in a WHERE clause
+        assert_eq!(query_rows("SELECT COUNT(*) FROM [./test/] a JOIN [./test/] b ON a.Size = b.Size * 1.0"), Ok(vec![vec![CoreValue::Integer(5)]]));
+        assert_eq!(query_rows("WITH p (Path) AS (SELECT './test/3.md') SELECT f.Name FROM p JOIN [./test/] f ON p.Path = f.Path"), Ok(vec![vec![string("3.md")]]));
+        assert_eq!(query_rows("WITH s (n) AS (SELECT '2'), i (n) AS (SELECT 2) SELECT COUNT(*) FROM s JOIN i ON s.n = i.n"), Ok(vec![vec![CoreValue::Integer(1)]]));
+    }
+
+    #[test]
+    fn consume_query_chained_joins() {
+        let query_rows = |sql: &str| super::consume_query(parse_query(sql), &QueryContext::default()).map(|result_set| result_set.rows);
+        let string = |str: &str| CoreValue::String(str.to_owned());
+
+        assert_eq!(query_rows("SELECT a.Name, b.Name, c.Name FROM [./test/] a JOIN [./test/] b ON a.Type = b.Type JOIN [./test/] c ON c.Name = b.Name AND c.Name <> a.Name ORDER BY a.Name"), Ok(vec![
+            vec![string("one"), string("two"), string("two")],
+            vec![string("two"), string("one"), string("one")],
+        ]));
+        // The third table is joined to the rows of the first two, keeping the columns of each
+        assert_eq!(query_rows("SELECT a.Name, b.Name, c.Name FROM [./test/] a LEFT JOIN [./test/] b ON a.Name = b.Name AND b.Type = 'file' RIGHT JOIN [./test/] c ON c.Name = a.Name AND c.Type = 'dir' ORDER BY c.Name"), Ok(vec![
+            vec![CoreValue::Null, CoreValue::Null, string("3.md")],
+            vec![string("one"), CoreValue::Null, string("one")],
+            vec![string("two"), CoreValue::Null, string("two")],
+        ]));
+        assert_eq!(query_rows("SELECT d.Name, f.Name FROM [./test/] d LEFT JOIN d.Path f ON f.Size >= 0 JOIN [./test/] e ON e.Name = d.Name ORDER BY d.Name"), Ok(vec![
+            vec![string("3.md"), CoreValue::Null],
+            vec![string("one"), string("1.txt")],
+            vec![string("two"), string("2.txt")],
+        ]));
+        assert!(query_rows("SELECT d.Name FROM [./test/] d RIGHT JOIN d.Path f ON f.Size >= 0").is_err());
+        assert!(query_rows("SELECT a.Name FROM [./test/] a JOIN [./test/] A ON a.Name = A.Name").is_err());
+    }
+
+    #[test]
+    fn combine_rows_all() {
+        let rows = |values: &[i64]| values.iter().map(|value| vec![CoreValue::Integer(*value)]).collect::<Vec<Vec<CoreValue>>>();
+
+        assert_eq!(combine_rows(&SetOperator::Intersect, true, rows(&[1, 1, 1, 2]), rows(&[1, 1, 3])), rows(&[1, 1]));
+        assert_eq!(combine_rows(&SetOperator::Intersect, false, rows(&[1, 1, 1, 2]), rows(&[1, 1, 3])), rows(&[1]));
+        assert_eq!(combine_rows(&SetOperator::Except, true, rows(&[1, 1, 1, 2]), rows(&[1, 3])), rows(&[1, 1, 2]));
+        assert_eq!(combine_rows(&SetOperator::Except, false, rows(&[1, 1, 1, 2]), rows(&[3])), rows(&[1, 2]));
+        assert_eq!(combine_rows(&SetOperator::Union, true, rows(&[1, 2]), rows(&[2])), rows(&[1, 2, 2]));
+        assert_eq!(combine_rows(&SetOperator::Union, false, rows(&[2, 1, 2]), rows(&[1, 3])), rows(&[2, 1, 3]));
+    }
+
+    #[test]
+    fn consume_op_timestamps() {
+        let created = CoreValue::Timestamp(std::time::UNIX_EPOCH + std::time::Duration::from_secs(86_400));
+        let day = evaluate_expr(&parse_expr("INTERVAL '1' DAY"), Scope::Row(&Row::default()), &QueryContext::default()).expect("Interval is valid");
+
+        assert_eq!(consume_op(created.clone(), &BinaryOperator::Minus, day, TimeZoneSetting::Utc), Ok(CoreValue::Timestamp(std::time::UNIX_EPOCH)));
+        assert_eq!(consume_op(created.clone(), &BinaryOperator::Minus, CoreValue::Timestamp(std::time::UNIX_EPOCH), TimeZoneSetting::Utc), Ok(CoreValue::Duration(std::time::Duration::from_secs(86_400))));
+        assert!(consume_op(created, &BinaryOperator::Plus, CoreValue::Integer(1), TimeZoneSetting::Utc).is_err());
+    }
+
+    #[test]
+    fn consume_op_gt_number_number() {
+        let result = consume_op(CoreValue::Integer(4096), &BinaryOperator::Gt, CoreValue::Integer(1000), TimeZoneSetting::Utc);
+
+        assert_eq!(result, Ok(CoreValue::Boolean(true)));
+    }
+
+    #[test]
+    fn consume_op_eq_number_null() {
+        let result = consume_op(CoreValue::Integer(2), &BinaryOperator::Eq, CoreValue::Null, TimeZoneSetting::Utc);
+
+        assert_eq!(result, Ok(CoreValue::Null));
+    }
+
+    #[test]
+    fn consume_op_and_null() {
+        assert_eq!(consume_op(CoreValue::Boolean(false), &BinaryOperator::And, CoreValue::Null, TimeZoneSetting::Utc), Ok(CoreValue::Boolean(false)));
+        assert_eq!(consume_op(CoreValue::Boolean(true), &BinaryOperator::And, CoreValue::Null, TimeZoneSetting::Utc), Ok(CoreValue::Null));
+        assert_eq!(consume_op(CoreValue::Boolean(true), &BinaryOperator::Or, CoreValue::Null, TimeZoneSetting::Utc), Ok(CoreValue::Boolean(true)));
+        assert!(consume_op(CoreValue::Boolean(false), &BinaryOperator::And, CoreValue::Integer(5), TimeZoneSetting::Utc).is_err());
+        assert!(consume_op(CoreValue::Integer(5), &BinaryOperator::Or, CoreValue::Boolean(true), TimeZoneSetting::Utc).is_err());
+        assert!(consume_op(CoreValue::Null, &BinaryOperator::And, CoreValue::String("a".to_owned()), TimeZoneSetting::Utc).is_err());
+    }
+
+    #[test]
+    fn evaluate_predicate_where() {
+        let expr = parse_expr("Size > 1000 AND (Type = 'file' OR NOT Name = 'a.txt')");
+        let large_file = vec![FileColumn::Name(Some(OsString::from("a.txt"))), FileColumn::Type(Some(file_type::FileType::File)), FileColumn::Size(Some(4096))];
+        let small_file = vec![FileColumn::Name(Some(OsString::from("b.txt"))), FileColumn::Type(Some(file_type::FileType::File)), FileColumn::Size(Some(10))];
+        let large_dir = vec![FileColumn::Name(Some(OsString::from("a.txt"))), FileColumn::Type(Some(file_type::FileType::Dir)), FileColumn::Size(Some(4096))];
+
+        assert_eq!(evaluate_predicate(&expr, Scope::Row(&Row::new("./", large_file.clone())), &QueryContext::default()), Ok(true));
+        assert_eq!(evaluate_predicate(&expr, Scope::Row(&Row::new("./", small_file.clone())), &QueryContext::default()), Ok(false));
+        assert_eq!(evaluate_predicate(&expr, Scope::Row(&Row::new("./", large_dir.clone())), &QueryContext::default()), Ok(false));
+    }
+
+    #[test]
+    fn evaluate_expr_qualified_columns() {
+        let row = Row::new("currdir", vec![FileColumn::Name(Some(OsString::from("a.txt")))])
+            .join(Row::new("prevdir", vec![FileColumn::Name(Some(OsString::from("b.txt")))]));
+
+        assert_eq!(evaluate_expr(&parse_expr("prevdir.Name"), Scope::Row(&row), &QueryContext::default()), Ok(CoreValue::String("b.txt".to_owned())));
+        assert_eq!(evaluate_predicate(&parse_expr("currdir.Name = 'a.txt'"), Scope::Row(&row), &QueryContext::default()), Ok(true));
+        assert!(evaluate_expr(&parse_expr("Name"), Scope::Row(&row), &QueryContext::default()).is_err());
+        assert!(evaluate_expr(&parse_expr("otherdir.Name"), Scope::Row(&row), &QueryContext::default()).is_err());
+    }
+
+    #[test]
+    fn projection_exprs_qualified_wildcard() {
+        let query = parse_query("SELECT currdir.Name, prevdir.* FROM [./] currdir INNER JOIN [../] prevdir ON prevdir.Type = currdir.Type");
+        let select = match &*query.body { SetExpr::Select(select) => { select } _ => { panic!("Incorrect enum variant expected") } };
+        let tables = table_with_joins_schemas(&select.from[0], Vec::new(), &QueryContext::default()).expect("Tables are valid");
+
+        let exprs = projection_exprs(&select.projection, &tables).expect("Select list is valid");
+
+        assert_eq!(tables, vec![
+            TableSchema { root: Some(PathBuf::from("./")), ..TableSchema::files("currdir") },
+            TableSchema { root: Some(PathBuf::from("../")), ..TableSchema::files("prevdir") },
+        ]);
+        assert_eq!(exprs.len(), 1 + FileColumn::iterator().count());
+        assert_eq!(exprs[1].1.to_string(), "prevdir.Name");
+        assert!(projection_exprs(&select.projection, &tables[..1]).is_err());
+    }
+
+    #[test]
+    fn evaluate_expr_case() {
+        let row = Row::new("./", vec![FileColumn::Name(Some(OsString::from("one"))), FileColumn::Type(Some(file_type::FileType::Dir)), FileColumn::Size(Some(4096))]);
+
+        assert_eq!(evaluate_expr(&parse_expr("CASE Type WHEN 'file' THEN 1 WHEN 'dir' THEN 2 END"), Scope::Row(&row), &QueryContext::default()), Ok(CoreValue::Integer(2)));
+        assert_eq!(evaluate_expr(&parse_expr("CASE WHEN Size > 10000 THEN 'large' WHEN Size > 0 THEN 'small' ELSE 'empty' END"), Scope::Row(&row), &QueryContext::default()), Ok(CoreValue::String("small".to_owned())));
+        assert_eq!(evaluate_expr(&parse_expr("CASE FileExtension WHEN NULL THEN 'none' END"), Scope::Row(&row), &QueryContext::default()), Ok(CoreValue::Null));
+    }
+
+    #[test]
+    fn project_rows_group_by_case() {
+        let query = parse_query("SELECT CASE WHEN Size > 100 THEN 'large' ELSE 'small' END AS class, COUNT(*) FROM [./] GROUP BY CASE WHEN Size > 100 THEN 'large' ELSE 'small' END ORDER BY class");
+        let rows = vec![
+            vec![FileColumn::Name(Some(OsString::from("a"))), FileColumn::Size(Some(2048))],
+            vec![FileColumn::Name(Some(OsString::from("b"))), FileColumn::Size(Some(10))],
+            vec![FileColumn::Name(Some(OsString::from("c"))), FileColumn::Size(Some(4096))],
+        ];
+
+        let result = select_rows(&query, rows).expect("Rows are groupable");
+
+        assert_eq!(result, vec![
+            vec![CoreValue::String("large".to_owned()), CoreValue::Integer(2)],
+            vec![CoreValue::String("small".to_owned()), CoreValue::Integer(1)],
+        ]);
+    }
+
+    #[test]
+    fn evaluate_predicate_null_size() {
+        let expr = parse_expr("Size > 1000");
+        let row = vec![FileColumn::Name(Some(OsString::from("a.txt"))), FileColumn::Size(None)];
+
+        assert_eq!(evaluate_predicate(&expr, Scope::Row(&Row::new("./", row.clone())), &QueryContext::default()), Ok(false));
+    }
+
+    #[test]
+    fn order_rows_multiple_keys() {
+        let query = parse_query("SELECT Name, Size AS bytes FROM [./] ORDER BY Type DESC, bytes DESC, 1");
+        let rows = vec![
+            vec![FileColumn::Name(Some(OsString::from("b"))), FileColumn::Type(Some(file_type::FileType::File)), FileColumn::Size(Some(10))],
+            vec![FileColumn::Name(Some(OsString::from("a"))), FileColumn::Type(Some(file_type::FileType::File)), FileColumn::Size(Some(10))],
+            vec![FileColumn::Name(Some(OsString::from("c"))), FileColumn::Type(Some(file_type::FileType::Dir)), FileColumn::Size(Some(4096))],
+            vec![FileColumn::Name(Some(OsString::from("d"))), FileColumn::Type(Some(file_type::FileType::File)), FileColumn::Size(Some(2048))],
+        ];
+
+        let result = select_rows(&query, rows).expect("Rows are sortable");
+
+        assert_eq!(first_column(result), vec!["d", "a", "b", "c"]);
+    }
+
+    #[test]
+    fn order_rows_nulls() {
+        let rows = vec![
+            vec![FileColumn::Name(Some(OsString::from("a"))), FileColumn::Size(None)],
+            vec![FileColumn::Name(Some(OsString::from("b"))), FileColumn::Size(Some(10))],
+            vec![FileColumn::Name(Some(OsString::from("c"))), FileColumn::Size(Some(20))],
+        ];
+        let names = |sql: &str| first_column(select_rows(&parse_query(sql), rows.clone()).expect("Rows are sortable"));
+
+        assert_eq!(names("SELECT Name FROM [./] ORDER BY Size"), vec!["a", "b", "c"]);
+        assert_eq!(names("SELECT Name FROM [./] ORDER BY Size DESC"), vec!["c", "b", "a"]);
+        assert_eq!(names("SELECT Name FROM [./] ORDER BY Size NULLS LAST"), vec!["b", "c", "a"]);
+        assert_eq!(names("SELECT Name FROM [./] ORDER BY Size DESC NULLS FIRST"), vec!["a", "c", "b"]);
+    }
+
+    #[test]
+    fn order_rows_position_out_of_range() {
+        let query = parse_query("SELECT Name FROM [./] ORDER BY 2");
+        let rows = vec![vec![FileColumn::Name(Some(OsString::from("a")))]];
+
+        assert!(select_rows(&query, rows).is_err());
+    }
+
+    #[test]
+    fn project_rows_columns() {
+        let query = parse_query("SELECT Name, Size > 10, Type AS kind, * FROM [./]");
+        let rows = vec![vec![FileColumn::Name(Some(OsString::from("a.rs"))), FileColumn::Type(Some(file_type::FileType::File)), FileColumn::Size(Some(20))]];
+
+        let result = project_query(&query, rows).expect("Rows are selectable");
+
+        assert_eq!(result.columns, vec!["Name", "Size > 10", "kind", "Name", "Path", "Type", "FileExtension", "Size", "AbsolutePath", "Parent", "Stem", "Extensions", "Depth", "RelativePath", "IsHidden", "Created", "Modified", "Accessed", "Changed", "LinkTarget", "IsBrokenLink", "Mode", "Permissions", "Uid", "Gid", "Owner", "Group", "Inode", "Device", "HardLinks", "Blocks"]);
+        assert_eq!(result.rows[0][..3].to_vec(), vec![CoreValue::String("a.rs".to_owned()), CoreValue::Boolean(true), CoreValue::String("file".to_owned())]);
+        assert_eq!(result.rows[0].len(), result.columns.len());
+    }
+
+    #[test]
+    fn project_rows_aliases() {
+        let query = parse_query("SELECT Size / 1024 AS kb, Name AS file FROM [./] ORDER BY kb DESC, file");
+        let rows = vec![
+            vec![FileColumn::Name(Some(OsString::from("a"))), FileColumn::Size(Some(2048))],
+            vec![FileColumn::Name(Some(OsString::from("c"))), FileColumn::Size(Some(4096))],
+            vec![FileColumn::Name(Some(OsString::from("b"))), FileColumn::Size(Some(2048))],
+        ];
+
+        let result = project_query(&query, rows).expect("Rows are selectable");
+
+        assert_eq!(result.columns, vec!["kb", "file"]);
+        assert_eq!(result.rows.iter().map(|row| row[0].to_string()).collect::<Vec<String>>(), vec!["4", "2", "2"]);
+        assert_eq!(result.rows.iter().map(|row| row[1].to_string()).collect::<Vec<String>>(), vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn project_rows_duplicate_alias() {
+        let query = parse_query("SELECT Name AS file, Path AS FILE FROM [./]");
+        let rows = vec![vec![FileColumn::Name(Some(OsString::from("a")))]];
+
+        assert!(select_rows(&query, rows).is_err());
+    }
+
+    #[test]
+    fn consume_op_divide_by_zero() {
+        assert_eq!(consume_op(CoreValue::Integer(4096), &BinaryOperator::Divide, CoreValue::Integer(1024), TimeZoneSetting::Utc), Ok(CoreValue::Integer(4)));
+        assert!(consume_op(CoreValue::Integer(4096), &BinaryOperator::Divide, CoreValue::Integer(0), TimeZoneSetting::Utc).is_err());
+    }
+
+    #[test]
+    fn project_rows_group_by() {
+        let query = parse_query("SELECT FileExtension, COUNT(*), SUM(Size), MAX(Name) AS last FROM [./] GROUP BY FileExtension ORDER BY SUM(Size) DESC");
+        let rows = vec![
+            vec![FileColumn::Name(Some(OsString::from("a.rs"))), FileColumn::FileExtension(Some(OsString::from("rs"))), FileColumn::Size(Some(10))],
+            vec![FileColumn::Name(Some(OsString::from("b.md"))), FileColumn::FileExtension(Some(OsString::from("md"))), FileColumn::Size(Some(5))],
+            vec![FileColumn::Name(Some(OsString::from("c.rs"))), FileColumn::FileExtension(Some(OsString::from("rs"))), FileColumn::Size(Some(20))],
+        ];
+
+        let result = select_rows(&query, rows).expect("Rows are groupable");
+
+        assert_eq!(result, vec![
+            vec![CoreValue::String("rs".to_owned()), CoreValue::Integer(2), CoreValue::Integer(30), CoreValue::String("c.rs".to_owned())],
+            vec![CoreValue::String("md".to_owned()), CoreValue::Integer(1), CoreValue::Integer(5), CoreValue::String("b.md".to_owned())],
+        ]);
+    }
+
+    #[test]
+    fn project_rows_having() {
+        let query = parse_query("SELECT FileExtension FROM [./] GROUP BY FileExtension HAVING SUM(Size) > 10 AND COUNT(*) > 1");
+        let rows = vec![
+            vec![FileColumn::FileExtension(Some(OsString::from("rs"))), FileColumn::Size(Some(10))],
+            vec![FileColumn::FileExtension(Some(OsString::from("md"))), FileColumn::Size(Some(50))],
+            vec![FileColumn::FileExtension(Some(OsString::from("rs"))), FileColumn::Size(Some(20))],
+            vec![FileColumn::FileExtension(Some(OsString::from("txt"))), FileColumn::Size(Some(1))],
+            vec![FileColumn::FileExtension(Some(OsString::from("txt"))), FileColumn::Size(Some(1))],
+        ];
+
+        assert_eq!(select_rows(&query, rows), Ok(vec![vec![CoreValue::String("rs".to_owned())]]));
+    }
+
+    #[test]
+    fn project_rows_having_without_group_by() {
+        let query = parse_query("SELECT COUNT(*) FROM [./] HAVING COUNT(*) > 1");
+        let rows = vec![vec![FileColumn::Size(Some(10))]];
+
+        assert_eq!(select_rows(&query, rows), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn project_rows_distinct() {
+        let query = parse_query("SELECT DISTINCT FileExtension FROM [./] ORDER BY FileExtension");
+        let rows = vec![
+            vec![FileColumn::FileExtension(Some(OsString::from("rs")))],
+            vec![FileColumn::FileExtension(None)],
+            vec![FileColumn::FileExtension(Some(OsString::from("md")))],
+            vec![FileColumn::FileExtension(None)],
+            vec![FileColumn::FileExtension(Some(OsString::from("rs")))],
+        ];
+
+        assert_eq!(select_rows(&query, rows), Ok(vec![
+            vec![CoreValue::Null],
+            vec![CoreValue::String("md".to_owned())],
+            vec![CoreValue::String("rs".to_owned())],
+        ]));
+    }
+
+    #[test]
+    fn project_rows_distinct_order_by_not_selected() {
+        let query = parse_query("SELECT DISTINCT FileExtension FROM [./] ORDER BY Size");
+
+        assert!(select_rows(&query, vec![vec![FileColumn::Size(Some(1))]]).is_err());
+    }
+
+    #[test]
+    fn project_rows_distinct_order_by_qualified() {
+        let rows = || vec![vec![FileColumn::Name(Some(OsString::from("b"))), FileColumn::Size(Some(1))], vec![FileColumn::Name(Some(OsString::from("a"))), FileColumn::Size(Some(2))]];
+
+        for sql in ["SELECT DISTINCT * FROM [./] ORDER BY Name", "SELECT DISTINCT d.Name FROM [./] d ORDER BY name", "SELECT DISTINCT Name FROM [./] d ORDER BY d.Name"] {
+            let names = select_rows(&parse_query(sql), rows()).expect("ORDER BY item is selected").into_iter().map(|row| row[0].clone()).collect::<Vec<CoreValue>>();
+            assert_eq!(names, vec![CoreValue::String("a".to_owned()), CoreValue::String("b".to_owned())], "{}", sql);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn project_rows_distinct_non_utf8_names() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let query = parse_query("SELECT DISTINCT Name FROM [./]");
+        let rows = vec![
+            vec![FileColumn::Name(Some(OsString::from_vec(vec![b'f', 0xff])))],
+            vec![FileColumn::Name(Some(OsString::from_vec(vec![b'f', 0xfe])))],
+            vec![FileColumn::Name(Some(OsString::from_vec(vec![b'f', 0xff])))],
+        ];
+
+        assert_eq!(select_rows(&query, rows).expect("Rows are distinct").len(), 2);
+    }
+
+    #[test]
+    fn contains_aggregate_nested() {
+        for expr in ["Name LIKE MAX(Name)", "Name NOT ILIKE MIN(Name)", "Name REGEXP MAX(Name)", "Name GLOB MIN(Name)", "COUNT(*) IN (SELECT Size FROM [./])", "SUM(Size) IS DISTINCT FROM 0", "-MAX(Size)"] {
+            assert!(contains_aggregate(&parse_expr(expr)), "{}", expr);
+        }
+
+        for expr in ["Name LIKE 'a%'", "Size IN (SELECT COUNT(*) FROM [./])", "EXISTS (SELECT COUNT(*) FROM [./])"] {
+            assert!(!contains_aggregate(&parse_expr(expr)), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn project_rows_aggregate_without_rows() {
+        let query = parse_query("SELECT COUNT(*), SUM(Size), COUNT(DISTINCT Type) FROM [./]");
+
+        assert_eq!(select_rows(&query, Vec::new()), Ok(vec![vec![CoreValue::Integer(0), CoreValue::Null, CoreValue::Integer(0)]]));
+    }
+
+    #[test]
+    fn project_rows_column_not_grouped() {
+        let query = parse_query("SELECT Name, COUNT(*) FROM [./] GROUP BY Type");
+        let rows = vec![vec![FileColumn::Name(Some(OsString::from("a"))), FileColumn::Type(Some(file_type::FileType::File))]];
+
+        assert!(select_rows(&query, rows).is_err());
+    }
+
+    #[test]
+    fn project_rows_group_by_qualified() {
+        let rows = || vec![
+            vec![FileColumn::Name(Some(OsString::from("a"))), FileColumn::Type(Some(file_type::FileType::File))],
+            vec![FileColumn::Name(Some(OsString::from("b"))), FileColumn::Type(Some(file_type::FileType::File))],
+        ];
+        let expected = Ok(vec![vec![CoreValue::String("file".to_owned()), CoreValue::Integer(2)]]);
+
+        assert_eq!(select_rows(&parse_query("SELECT f.Type, COUNT(*) FROM [./] f GROUP BY Type"), rows()), expected);
+        assert_eq!(select_rows(&parse_query("SELECT type, COUNT(*) FROM [./] f GROUP BY F.Type"), rows()), expected);
+        assert_eq!(select_rows(&parse_query("SELECT f.Type, COUNT(*) FROM [./] f GROUP BY f.Type ORDER BY COUNT(*)"), rows()), expected);
+        assert_eq!(select_rows(&parse_query("SELECT 1 FROM [./] ORDER BY COUNT(*)"), rows()), Ok(vec![vec![CoreValue::Integer(1)]]));
+        assert!(select_rows(&parse_query("SELECT f.Name FROM [./] f GROUP BY Type"), rows()).is_err());
+    }
+
+    #[test]
+    fn limit_rows_offset_fetch() {
+        let query = parse_query("SELECT Name FROM [./] ORDER BY Size OFFSET 1 ROWS FETCH FIRST 2 ROWS ONLY");
+        let row_limit = RowLimit::from_query(query.limit.as_ref(), query.offset.as_ref(), query.fetch.as_ref(), &QueryContext::default()).expect("Limit is valid");
+        let rows = (0..5).map(|size| (vec![CoreValue::Integer(size)], vec![CoreValue::Integer(size)])).collect();
+
+        let result = limit_rows(rows, &row_limit, query.order_by.as_ref().map(|order_by| &order_by.exprs[..]).unwrap_or(&[]), TimeZoneSetting::Utc);
+
+        assert_eq!(result.into_iter().map(|(_, row)| row).collect::<Vec<Vec<CoreValue>>>(), vec![vec![CoreValue::Integer(1)], vec![CoreValue::Integer(2)]]);
+    }
+
+    #[test]
+    fn limit_rows_with_ties() {
+        let query = parse_query("SELECT TOP 2 WITH TIES Name FROM [./] ORDER BY Size");
+        let top = match &*query.body { SetExpr::Select(select) => { select.top.clone().expect("TOP is set") } _ => { panic!("Incorrect enum variant expected") } };
+        let row_limit = RowLimit::from_top(&top, &QueryContext::default()).expect("Limit is valid");
+        let rows = vec![1, 2, 2, 2, 3].into_iter().map(|size| (vec![CoreValue::Integer(size)], Vec::new())).collect();
+
+        assert_eq!(limit_rows(rows, &row_limit, query.order_by.as_ref().map(|order_by| &order_by.exprs[..]).unwrap_or(&[]), TimeZoneSetting::Utc).len(), 4);
+    }
+
+    #[test]
+    fn limit_rows_percent() {
+        let query = parse_query("SELECT TOP 50 PERCENT Name FROM [./]");
+        let top = match &*query.body { SetExpr::Select(select) => { select.top.clone().expect("TOP is set") } _ => { panic!("Incorrect enum variant expected") } };
+        let row_limit = RowLimit::from_top(&top, &QueryContext::default()).expect("Limit is valid");
+        let rows = (0..5).map(|_| (Vec::new(), Vec::new())).collect();
+
+        // Rounds up like MSSQL
+        assert_eq!(limit_rows(rows, &row_limit, &[], TimeZoneSetting::Utc).len(), 3);
+    }
+
+    #[test]
+    fn row_limit_scan_limit() {
+        let query = parse_query("SELECT Name FROM [./] LIMIT 2 OFFSET 3");
+        let row_limit = RowLimit::from_query(query.limit.as_ref(), query.offset.as_ref(), query.fetch.as_ref(), &QueryContext::default()).expect("Limit is valid");
+
+        assert_eq!(row_limit.scan_limit(), Some(5));
+    }
+
+    #[test]
+    fn scan_relation_stops_at_limit() {
+        let query = parse_query(&format!("SELECT Name FROM [{}]", PATH_TO_TEST_DIR));
+        let relation = match *query.body { SetExpr::Select(select) => { select.from[0].relation.clone() } _ => { panic!("Incorrect enum variant expected") } };
+        let schema = relation_schema(&relation, &[], &QueryContext::default()).expect("Table is valid");
+
+        assert_eq!(scan_relation(&relation, &schema, None, Some(2), &QueryContext::default()).expect("Directory is readable").len(), 2);
+    }
+
+    fn parse_query(sql: &str) -> Query {
+        match Parser::parse_sql(&dialect::CoreDialect::from_str("mssql").expect("Dialect exists"), sql).expect("SQL is valid").remove(0) {
+            Statement::Query(query) => { *query }
+            _ => { panic!("Incorrect enum variant expected") }
+        }
+    }
+
+    fn select_rows(query: &Query, rows: Vec<Vec<FileColumn>>) -> Result<Vec<Vec<CoreValue>>, CoreError> {
+        project_query(query, rows).map(|result_set| result_set.rows)
+    }
+
+    fn project_query(query: &Query, rows: Vec<Vec<FileColumn>>) -> Result<ResultSet, CoreError> {
+        let row_limit = RowLimit::from_query(query.limit.as_ref(), query.offset.as_ref(), query.fetch.as_ref(), &QueryContext::default())?;
+        match &*query.body {
+            SetExpr::Select(select) => {
+                let tables = table_with_joins_schemas(&select.from[0], Vec::new(), &QueryContext::default())?;
+                let rows = rows.into_iter().map(|columns| Row::new(&tables[0].name, columns)).collect();
+
+                project_rows(rows, select, &tables, query.order_by.as_ref().map(|order_by| &order_by.exprs[..]).unwrap_or(&[]), &row_limit, &QueryContext::default())
+            }
+            _ => { panic!("Incorrect enum variant expected") }
+        }
+    }
+
+    fn first_column(rows: Vec<Vec<CoreValue>>) -> Vec<String> {
+        rows.into_iter().map(|row| row[0].to_string()).collect()
+    }
+
+    fn parse_expr(expr: &str) -> Expr {
+        let query = parse_query(&format!("SELECT * FROM [{}] WHERE {}", PATH_TO_TEST_DIR, expr));
+        match *query.body {
+            SetExpr::Select(select) => { select.selection.expect("WHERE clause is set") }
             _ => { panic!("Incorrect enum variant expected") }
-        }, "0".to_owned());
+        }
     }
 
     #[test]
-    fn consume_table_name() {
-        let files = super::consume_table_name(PATH_TO_TEST_DIR);
+    fn scan_table_name() {
+        let files = super::scan_table_name(PATH_TO_TEST_DIR);
 
-        assert_eq!(files.expect("Directory is readable").len(), 3);
+        assert_eq!(files.expect("Directory is readable").count(), 3);
     }
 
     // #[test]
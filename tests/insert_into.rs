@@ -0,0 +1,23 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use std::{env, fs, process::Command}; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn insert_into_csv_with_aliases() -> Result<(), Box<dyn std::error::Error>> {
+    let output_path = env::temp_dir().join("systemsql_insert_into.csv");
+    let sql = format!("INSERT INTO [{output}] SELECT Name AS file, Size / 1024 AS kb FROM [{test_dir}] ORDER BY file", output = output_path.display(), test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert().success();
+
+    let csv = fs::read_to_string(&output_path)?;
+    fs::remove_file(&output_path)?;
+    assert_eq!(csv.lines().collect::<Vec<&str>>(), vec!["file,kb", "3.md,0", "one,4", "two,4"]);
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn select_columns_where() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT Name, Type FROM [{test_dir}] WHERE Type = 'file'", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("Name | Type").and(predicate::str::contains("3.md | file")).and(predicate::str::contains("one").not()));
+
+    Ok(())
+}
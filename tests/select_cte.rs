@@ -0,0 +1,72 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn select_recursive_cte() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("WITH RECURSIVE tree (Path, Type) AS (SELECT Path, Type FROM [{test_dir}] UNION ALL SELECT c.Path, c.Type FROM tree t, t.Path c) SELECT Path FROM tree WHERE Type = 'file' ORDER BY Path", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("./test/3.md\n./test/one/1.txt\n./test/two/2.txt"));
+
+    Ok(())
+}
+
+#[test]
+fn select_recursive_cte_max_recursion() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = "WITH RECURSIVE r (n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM r WHERE n < 200) SELECT MAX(n) FROM r";
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("The maximum recursion 100 has been exhausted"));
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(format!("SET MAXRECURSION = 1000; {}", sql));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("200"));
+
+    Ok(())
+}
+
+#[test]
+fn select_cte_unqualified_columns() -> Result<(), Box<dyn std::error::Error>> {
+    // A column only one of the tables has does not need to be qualified
+    let sql = format!("WITH a (x) AS (SELECT 1) SELECT x, Name FROM a, [{test_dir}] WHERE Name = '3.md'", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1 | 3.md"));
+
+    Ok(())
+}
+
+#[test]
+fn select_cte_group_by_unqualified_column() -> Result<(), Box<dyn std::error::Error>> {
+    // Type is the directory's column whether or not it is qualified
+    let sql = format!("WITH c (n) AS (SELECT 1) SELECT f.Type, COUNT(*) FROM c, [{test_dir}] f GROUP BY Type ORDER BY COUNT(*) DESC", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("dir  | 2\nfile | 1\n"));
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn select_duplicate_files() -> Result<(), Box<dyn std::error::Error>> {
+    // The test directory is shared, so the duplicates are made in a directory of their own
+    let dir = std::env::temp_dir().join(format!("systemsql_select_duplicate_files_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("sub"))?;
+    std::fs::write(dir.join("a.txt"), "abc")?;
+    std::fs::write(dir.join("b.txt"), "abc")?;
+    std::fs::write(dir.join("c.txt"), "abd")?;
+
+    let sql = format!("SELECT Hash_Sha256, COUNT(*) AS Copies FROM [{}/] WHERE Type = 'file' GROUP BY Hash_Sha256 HAVING COUNT(*) > 1", dir.display());
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    let assert = cmd.assert();
+    std::fs::remove_dir_all(&dir)?;
+    assert
+        .success()
+        .stdout(predicate::str::contains("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad | 2"))
+        .stdout(predicate::str::contains("| 1").not());
+
+    Ok(())
+}
+
+#[test]
+fn select_hash_of_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT Name, Hash_Crc32 FROM [{test_dir}] WHERE Type = 'dir' ORDER BY Name", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("one  | NULL\ntwo  | NULL"));
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn select_full_join_using() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT Name, a.Path, b.Path FROM [{test_dir}one/] a FULL JOIN [{test_dir}two/] b USING (Name) ORDER BY Name", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1.txt | ./test/one/1.txt | NULL\n2.txt | NULL             | ./test/two/2.txt"));
+
+    Ok(())
+}
+
+#[test]
+fn select_cross_join() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT a.Name, b.Name FROM [{test_dir}one/] a CROSS JOIN [{test_dir}two/] b", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1.txt | 2.txt"));
+
+    Ok(())
+}
+
+#[test]
+fn select_join_compound_condition() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT f.Name, d.Name FROM [{test_dir}] f INNER JOIN [{test_dir}] d ON d.Type = f.Type AND d.Name > f.Name WHERE f.Type = 'dir'", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("one  | two"));
+
+    Ok(())
+}
+
+#[test]
+fn select_chained_joins() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT d.Name, f.Name, c.Name FROM [{test_dir}] d JOIN d.Path f ON f.Type = 'file' LEFT JOIN [{test_dir}one/] c ON c.Name = f.Name ORDER BY d.Name", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("one  | 1.txt | 1.txt\ntwo  | 2.txt | NULL"));
+
+    Ok(())
+}
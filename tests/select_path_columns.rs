@@ -0,0 +1,35 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn select_relative_path_and_depth() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT c.RelativePath, c.Depth, c.Stem FROM [{test_dir}] d, d.Path c ORDER BY c.RelativePath", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("one/1.txt    | 2     | 1\ntwo/2.txt    | 2     | 2"));
+
+    Ok(())
+}
+
+#[test]
+fn select_absolute_path() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT AbsolutePath FROM [{test_dir}] WHERE Name = '3.md' AND NOT IsHidden", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(std::fs::canonicalize(PATH_TO_TEST_DIR)?.join("3.md").to_string_lossy().into_owned()));
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn select_name_like() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT Name FROM [{test_dir}] WHERE Name LIKE '%.md' OR Name GLOB 't?o'", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("3.md").and(predicate::str::contains("two")).and(predicate::str::contains("one").not()));
+
+    Ok(())
+}
+
+#[test]
+fn select_name_regexp() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT Name FROM [{test_dir}] WHERE Name NOT REGEXP '^t'", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("3.md").and(predicate::str::contains("one")).and(predicate::str::contains("two").not()));
+
+    Ok(())
+}
+
+#[test]
+fn select_name_like_brackets() -> Result<(), Box<dyn std::error::Error>> {
+    // MS SQL reads [o] as a set of characters, other dialects match the brackets themselves
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(format!("SELECT Name FROM [{test_dir}] WHERE Name LIKE '[o]%'", test_dir = PATH_TO_TEST_DIR));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("one"));
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(format!("SELECT Name FROM \"{test_dir}\" WHERE Name LIKE '[o]%'", test_dir = PATH_TO_TEST_DIR)).arg("generic");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("one").not());
+
+    Ok(())
+}
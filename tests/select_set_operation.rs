@@ -0,0 +1,35 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn select_union_except() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT Name FROM [{test_dir}one/] UNION SELECT Name FROM [{test_dir}two/] EXCEPT SELECT Name FROM [{test_dir}two/] ORDER BY Name", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1.txt").and(predicate::str::contains("2.txt").not()));
+
+    Ok(())
+}
+
+#[test]
+fn select_union_column_count() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT Name, Size FROM [{test_dir}] UNION SELECT Name FROM [{test_dir}]", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("must have an equal number of expressions"));
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn select_correlated_not_exists() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT f.Name FROM [{test_dir}one/] f WHERE NOT EXISTS (SELECT 1 FROM [{test_dir}two/] d WHERE d.Name = f.Name)", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1.txt").and(predicate::str::contains("2.txt").not()));
+
+    Ok(())
+}
+
+#[test]
+fn select_scalar_subquery() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT Name, (SELECT COUNT(*) FROM [{test_dir}] WHERE Type = 'dir') AS dirs FROM [{test_dir}] WHERE Name IN (SELECT Name FROM [{test_dir}] WHERE FileExtension = 'md')", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("3.md | 2").and(predicate::str::contains("one").not()));
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PROGRAM_NAME: &str = "systemsql";
+
+#[cfg(unix)]
+#[test]
+fn select_broken_links() -> Result<(), Box<dyn std::error::Error>> {
+    // The test directory is shared, so the links are made in a directory of their own
+    let dir = std::env::temp_dir().join(format!("systemsql_select_broken_links_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("a.txt"), "a")?;
+    std::os::unix::fs::symlink("a.txt", dir.join("link"))?;
+    std::os::unix::fs::symlink("missing.txt", dir.join("broken"))?;
+
+    let sql = format!("SELECT Name, Type, LinkTarget FROM [{}/] WHERE IsBrokenLink ORDER BY Name", dir.display());
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    let assert = cmd.assert();
+    std::fs::remove_dir_all(&dir)?;
+    assert
+        .success()
+        .stdout(predicate::str::contains("broken | symlink | missing.txt"))
+        .stdout(predicate::str::contains("\nlink ").not());
+
+    Ok(())
+}
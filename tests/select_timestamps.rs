@@ -0,0 +1,35 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn select_modified_since_date() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT COUNT(*) AS Files FROM [{test_dir}] WHERE Modified > '1970-01-01' AND Accessed IS NOT NULL AND Changed >= Modified", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Files\n-----\n3"));
+
+    Ok(())
+}
+
+#[test]
+fn select_timestamp_utc() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SET TIME ZONE 'UTC'; SELECT TOP 1 Modified FROM [{test_dir}]", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z")?);
+
+    Ok(())
+}
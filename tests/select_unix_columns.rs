@@ -0,0 +1,74 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[cfg(unix)]
+#[test]
+fn select_permissions() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT Name, Mode, Permissions, HardLinks FROM [{test_dir}] WHERE Name = '3.md'", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(r"3\.md \| 0[0-7]{3} \| [r-][w-][x-][r-][w-][x-][r-][w-][x-] +\| 1")?);
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn select_owner() -> Result<(), Box<dyn std::error::Error>> {
+    let sql = format!("SELECT COUNT(*) AS Owned FROM [{test_dir}] WHERE Owner IS NOT NULL AND Uid >= 0 AND Inode > 0", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Owned\n-----\n3"));
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn select_mode_mask() -> Result<(), Box<dyn std::error::Error>> {
+    // Mode is a number, so its bits can be tested with a mask
+    let sql = format!("SELECT COUNT(*) AS Readable FROM [{test_dir}] WHERE Mode & 256 = 256 AND Mode & 4095 = Mode", test_dir = PATH_TO_TEST_DIR);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(sql);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Readable\n--------\n3"));
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn select_mode_aliases() -> Result<(), Box<dyn std::error::Error>> {
+    // The mode is displayed in octal whatever it is named, and another column named Mode is not
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(format!("SELECT Mode AS m FROM [{test_dir}] WHERE Name = '3.md'", test_dir = PATH_TO_TEST_DIR));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(r"m\n-+\n0[0-7]{3}\n")?);
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(format!("SELECT 4096 AS Mode, HardLinks FROM [{test_dir}] WHERE Name = '3.md'", test_dir = PATH_TO_TEST_DIR));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("4096 | 1\n"));
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::process::Command; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn show_columns() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(format!("SHOW COLUMNS FROM [{test_dir}]", test_dir = PATH_TO_TEST_DIR));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("Columns: Name, Path, Type, FileExtension, Size,"))
+        .stdout(predicate::str::contains(", Blocks, Hash_Sha256, Hash_Blake3, Hash_Md5, Hash_Crc32\n"));
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use std::{env, fs, process::Command}; // Run programs
+
+// TODO! Move these to a shared module
+const PATH_TO_TEST_DIR: &str = "./test/";
+const PROGRAM_NAME: &str = "systemsql";
+
+#[test]
+fn update_name() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = env::temp_dir().join(format!("systemsql_update_name_{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("a.txt"), "a")?;
+    fs::write(dir.join("b.txt"), "b")?;
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(format!("UPDATE [{dir}] SET Name = Stem + '.md' WHERE Name = 'a.txt'", dir = dir.display()));
+    cmd.assert().success();
+
+    let mut names = fs::read_dir(&dir)?.map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned())).collect::<Result<Vec<String>, _>>()?;
+    names.sort();
+    assert_eq!(names, vec!["a.md", "b.txt"]);
+
+    // A name that is taken leaves every file as it was
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(format!("UPDATE [{dir}] SET Name = 'b.txt' WHERE Name = 'a.md'", dir = dir.display()));
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("already exists"));
+    assert!(dir.join("a.md").exists());
+
+    fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
+
+#[test]
+fn update_without_matches() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(format!("UPDATE [{test_dir}] SET Name = 'x' WHERE Name = 'y'", test_dir = PATH_TO_TEST_DIR));
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn unsupported_statements() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(format!("UPDATE [{test_dir}] SET Size = 0", test_dir = PATH_TO_TEST_DIR));
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("only the Name of a file can be updated"));
+
+    let mut cmd = Command::cargo_bin(PROGRAM_NAME)?;
+
+    cmd.arg(format!("DELETE FROM [{test_dir}]", test_dir = PATH_TO_TEST_DIR));
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("is not supported"));
+
+    Ok(())
+}